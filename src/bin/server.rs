@@ -0,0 +1,14 @@
+use my_redis::Config;
+use tokio::net::TcpListener;
+use tokio::signal;
+
+#[tokio::main]
+async fn main() {
+    // リスナーをアドレスにバインド
+    let listener = TcpListener::bind("127.0.0.1:6379").await.unwrap();
+
+    println!("リスニングしています...");
+
+    // Ctrl-Cを受け取るまでコネクションを処理
+    my_redis::server::run(listener, Config::default(), signal::ctrl_c()).await;
+}
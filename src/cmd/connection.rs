@@ -0,0 +1,31 @@
+//! コネクションの状態を確認するコマンド
+//!
+//! これらのコマンドはデータベースを参照しないため、ロックを取得しない。
+
+use super::Context;
+use crate::parse::{Parse, ParseError};
+use crate::Frame;
+
+/// `PING [message]`
+///
+/// `message`を指定しない場合は`PONG`を、指定した場合は`message`をそのまま返す。
+pub(crate) fn ping(_cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    if !parse.has_remaining() {
+        return Ok(Frame::Simple("PONG".into()));
+    }
+
+    let message = parse.next_bytes()?;
+    parse.finish()?;
+
+    Ok(Frame::Bulk(message))
+}
+
+/// `ECHO message`
+///
+/// `message`をそのまま返す。
+pub(crate) fn echo(_cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let message = parse.next_bytes()?;
+    parse.finish()?;
+
+    Ok(Frame::Bulk(message))
+}
@@ -0,0 +1,117 @@
+//! ハッシュの値を操作するコマンド
+
+use super::{Context, WRONGTYPE};
+use crate::db::{live_entry, Entry, Value};
+use crate::parse::{Parse, ParseError};
+use crate::Frame;
+use std::collections::HashMap;
+
+/// `HSET key field value [field value ...]`
+///
+/// ハッシュのフィールドに値を設定して、新たに追加したフィールドの数を返す。
+/// キーが存在しない場合は空のハッシュとして扱い、キーに設定されていた期限は維持する。
+pub(crate) fn hset(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    let mut pairs = vec![(parse.next_bytes()?, parse.next_bytes()?)];
+    while parse.has_remaining() {
+        pairs.push((parse.next_bytes()?, parse.next_bytes()?));
+    }
+
+    let mut db = cx.db.lock().unwrap();
+    if live_entry(&mut db, &key).is_none() {
+        db.insert(key.clone(), Entry::new(Value::Hash(HashMap::new())));
+    }
+    let entry = db.get_mut(&key).unwrap();
+    let hash = match &mut entry.value {
+        Value::Hash(hash) => hash,
+        _ => return Ok(Frame::Error(WRONGTYPE.into())),
+    };
+
+    let added = pairs
+        .into_iter()
+        .filter(|(field, value)| hash.insert(field.clone(), value.clone()).is_none())
+        .count();
+    entry.mark_updated();
+
+    Ok(Frame::Integer(added as i64))
+}
+
+/// `HGET key field`
+///
+/// ハッシュのフィールドの値を返す。キーまたはフィールドが存在しない場合は`Null`を返す。
+pub(crate) fn hget(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    let field = parse.next_bytes()?;
+    parse.finish()?;
+
+    let mut db = cx.db.lock().unwrap();
+    let entry = match live_entry(&mut db, &key) {
+        Some(entry) => entry,
+        None => return Ok(Frame::Null),
+    };
+    entry.touch();
+
+    match &entry.value {
+        Value::Hash(hash) => Ok(hash.get(&field).cloned().map_or(Frame::Null, Frame::Bulk)),
+        _ => Ok(Frame::Error(WRONGTYPE.into())),
+    }
+}
+
+/// `HDEL key field [field ...]`
+///
+/// ハッシュからフィールドを削除して、実際に削除したフィールドの数を返す。
+/// 全てのフィールドを削除した場合は、キーも削除する。
+pub(crate) fn hdel(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    let mut fields = vec![parse.next_bytes()?];
+    while parse.has_remaining() {
+        fields.push(parse.next_bytes()?);
+    }
+
+    let mut db = cx.db.lock().unwrap();
+    let entry = match live_entry(&mut db, &key) {
+        Some(entry) => entry,
+        None => return Ok(Frame::Integer(0)),
+    };
+    let hash = match &mut entry.value {
+        Value::Hash(hash) => hash,
+        _ => return Ok(Frame::Error(WRONGTYPE.into())),
+    };
+
+    let removed = fields
+        .iter()
+        .filter(|field| hash.remove(*field).is_some())
+        .count();
+    if hash.is_empty() {
+        db.remove(&key);
+    } else if removed > 0 {
+        entry.mark_updated();
+    }
+
+    Ok(Frame::Integer(removed as i64))
+}
+
+/// `HGETALL key`
+///
+/// ハッシュの全てのフィールドと値を、フィールド、値の順に並べた配列で返す。
+/// フィールドの順序は決まっていない。キーが存在しない場合は空の配列を返す。
+pub(crate) fn hgetall(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    parse.finish()?;
+
+    let mut db = cx.db.lock().unwrap();
+    let entry = match live_entry(&mut db, &key) {
+        Some(entry) => entry,
+        None => return Ok(Frame::Array(vec![])),
+    };
+    entry.touch();
+
+    match &entry.value {
+        Value::Hash(hash) => Ok(Frame::Array(
+            hash.iter()
+                .flat_map(|(field, value)| [Frame::Bulk(field.clone()), Frame::Bulk(value.clone())])
+                .collect(),
+        )),
+        _ => Ok(Frame::Error(WRONGTYPE.into())),
+    }
+}
@@ -0,0 +1,476 @@
+//! キー空間を操作するコマンド
+
+use super::{invalid_expire_time, Context, NO_SUCH_KEY, SYNTAX_ERROR};
+use crate::db::{deadline_after, drop_in_background, instant_at, live_entry, Entry};
+use crate::glob;
+use crate::parse::{Parse, ParseError};
+use crate::{Db, Frame};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::UNIX_EPOCH;
+use tokio::time::{Duration, Instant};
+
+/// `DEL key [key ...]`
+///
+/// 指定したキーを削除して、実際に削除したキーの数を返す。
+pub(crate) fn del(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let keys = parse.next_strings()?;
+
+    // 期限切れのエントリは削除しても数えない
+    let now = Instant::now();
+    let mut db = cx.db.lock().unwrap();
+    let removed = keys
+        .iter()
+        .filter(|key| db.remove(key).is_some_and(|entry| !entry.is_expired(now)))
+        .count();
+
+    Ok(Frame::Integer(removed as i64))
+}
+
+/// `COPY source destination [REPLACE]`
+///
+/// `source`の値を`destination`に複製する。期限は複製しない。
+/// 複製した場合は1、`source`が存在しないか、`REPLACE`を指定せず`destination`が既に存在する場合は0を返す。
+pub(crate) fn copy(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let src = parse.next_string()?;
+    let dst = parse.next_string()?;
+
+    let mut replace = false;
+    while parse.has_remaining() {
+        match &parse.next_string()?.to_uppercase()[..] {
+            "REPLACE" => replace = true,
+            _ => return Ok(Frame::Error(SYNTAX_ERROR.into())),
+        }
+    }
+
+    if src == dst {
+        return Ok(Frame::Error(
+            "ERR source and destination objects are the same".into(),
+        ));
+    }
+
+    // 存在の確認と複製を同じロックの中で行う
+    let mut db = cx.db.lock().unwrap();
+    if !replace && live_entry(&mut db, &dst).is_some() {
+        return Ok(Frame::Integer(0));
+    }
+    // `Bytes`の複製は参照カウントを増やすだけで、値はどちらからも変更できない
+    let value = match live_entry(&mut db, &src) {
+        Some(entry) => entry.value.clone(),
+        None => return Ok(Frame::Integer(0)),
+    };
+    db.insert(dst, Entry::new(value));
+
+    Ok(Frame::Integer(1))
+}
+
+/// `UNLINK key [key ...]`
+///
+/// `DEL`と同様に指定したキーを削除して、実際に削除したキーの数を返す。
+/// ロックを保持している間はマップからエントリを取り除くだけにして、値の破棄はバックグラウンドで行う。
+pub(crate) fn unlink(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let keys = parse.next_strings()?;
+
+    let now = Instant::now();
+    let mut entries = cx.db.lock().unwrap();
+    let removed: Vec<_> = keys.iter().filter_map(|key| entries.remove(key)).collect();
+    drop(entries);
+
+    // 期限切れのエントリは削除しても数えない
+    let count = removed
+        .iter()
+        .filter(|entry| !entry.is_expired(now))
+        .count();
+    if !removed.is_empty() {
+        drop_in_background(removed);
+    }
+
+    Ok(Frame::Integer(count as i64))
+}
+
+/// `EXISTS key [key ...]`
+///
+/// 指定したキーのうち存在するものの数を返す。同じキーを複数回指定した場合は、その回数だけ数える。
+pub(crate) fn exists(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let keys = parse.next_strings()?;
+
+    let mut db = cx.db.lock().unwrap();
+    let count = keys
+        .iter()
+        .filter(|key| live_entry(&mut db, key).is_some())
+        .count();
+
+    Ok(Frame::Integer(count as i64))
+}
+
+/// `TOUCH key [key ...]`
+///
+/// 指定したキーにアクセスした時刻を更新して、存在するキーの数を返す。
+pub(crate) fn touch(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let keys = parse.next_strings()?;
+
+    let mut db = cx.db.lock().unwrap();
+    let count = keys
+        .iter()
+        .filter(|key| {
+            live_entry(&mut db, key)
+                .map(|entry| entry.touch())
+                .is_some()
+        })
+        .count();
+
+    Ok(Frame::Integer(count as i64))
+}
+
+/// `KEYS pattern`
+///
+/// パターンに一致する全てのキーを返す。期限切れのキーは含まない。
+pub(crate) fn keys(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let pattern = parse.next_string()?;
+    parse.finish()?;
+
+    let now = Instant::now();
+    let db = cx.db.lock().unwrap();
+    let keys = db
+        .iter()
+        .filter(|(key, entry)| {
+            !entry.is_expired(now) && glob::matches(pattern.as_bytes(), key.as_bytes())
+        })
+        .map(|(key, _)| Frame::Bulk(key.clone().into()))
+        .collect();
+
+    Ok(Frame::Array(keys))
+}
+
+/// `RANDOMKEY`
+///
+/// 期限切れでないキーを無作為に1つ選んで返す。キーが存在しない場合は`Null`を返す。
+///
+/// `HashMap`からは位置を指定してキーを取り出せないため、呼び出しごとに全てのエントリを走査する(O(n))。
+pub(crate) fn randomkey(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    parse.finish()?;
+
+    let now = Instant::now();
+    let db = cx.db.lock().unwrap();
+    let mut live = db.iter().filter(|(_, entry)| !entry.is_expired(now));
+
+    let count = live.clone().count();
+    if count == 0 {
+        return Ok(Frame::Null);
+    }
+    let (key, _) = live.nth(random_index(count)).unwrap();
+
+    Ok(Frame::Bulk(key.clone().into()))
+}
+
+/// `0`以上`len`未満の添字を無作為に返す。
+///
+/// 乱数には、標準ライブラリの`RandomState`がハッシュ関数ごとに生成する鍵を利用する。
+fn random_index(len: usize) -> usize {
+    let random = RandomState::new().build_hasher().finish();
+
+    (random % len as u64) as usize
+}
+
+/// `RENAME key newkey`
+///
+/// キーの値と期限を`newkey`に移す。`newkey`が既に存在する場合は上書きする。
+/// `key`と`newkey`が同じ場合は何もしない。
+pub(crate) fn rename(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let src = parse.next_string()?;
+    let dst = parse.next_string()?;
+    parse.finish()?;
+
+    // 移動元の削除と移動先への挿入を同じロックの中で行う
+    let mut db = cx.db.lock().unwrap();
+    if live_entry(&mut db, &src).is_none() {
+        return Ok(Frame::Error(NO_SUCH_KEY.into()));
+    }
+    if src != dst {
+        let entry = db.remove(&src).unwrap();
+        db.insert(dst, entry);
+    }
+
+    Ok(Frame::Simple("OK".into()))
+}
+
+/// `RENAMENX key newkey`
+///
+/// `newkey`が存在しない場合に限り、キーの値と期限を`newkey`に移す。
+/// 移した場合は1、`newkey`が既に存在する場合は0を返す。
+pub(crate) fn renamenx(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let src = parse.next_string()?;
+    let dst = parse.next_string()?;
+    parse.finish()?;
+
+    let mut db = cx.db.lock().unwrap();
+    if live_entry(&mut db, &src).is_none() {
+        return Ok(Frame::Error(NO_SUCH_KEY.into()));
+    }
+    if live_entry(&mut db, &dst).is_some() {
+        return Ok(Frame::Integer(0));
+    }
+    let entry = db.remove(&src).unwrap();
+    db.insert(dst, entry);
+
+    Ok(Frame::Integer(1))
+}
+
+/// `SCAN cursor [MATCH pattern] [COUNT count]`
+///
+/// カーソルから始まる`count`個(既定は10個)のキーを調べて、次のカーソルとパターンに一致したキーを返す。
+/// 次のカーソルが0の場合は、全てのキーを調べ終えたことを表す。
+///
+/// キーは固定のハッシュ値の順に調べ、カーソルは次に調べるキーのハッシュ値とする。
+/// キーの追加や削除によって他のキーの順序が変わらないため、走査の間ずっと存在するキーは必ず1回以上返される。
+/// 1回の呼び出しはハッシュ値の索引を`count`個辿るだけで、呼び出しごとにロックを解放するため、
+/// 走査中に他のクライアントがキーを操作できる。
+pub(crate) fn scan(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let cursor = match parse.next_string()?.parse::<u64>() {
+        Ok(cursor) => cursor,
+        Err(_) => return Ok(Frame::Error("ERR invalid cursor".into())),
+    };
+
+    let mut pattern = None;
+    let mut count = 10;
+    while parse.has_remaining() {
+        let option = parse.next_string()?.to_uppercase();
+        match &option[..] {
+            "MATCH" => pattern = Some(parse.next_string()?),
+            "COUNT" => {
+                count = parse.next_int()?;
+                if count < 1 {
+                    return Ok(Frame::Error(SYNTAX_ERROR.into()));
+                }
+            }
+            _ => return Ok(Frame::Error(SYNTAX_ERROR.into())),
+        }
+    }
+
+    let now = Instant::now();
+    let db = cx.db.lock().unwrap();
+    let (candidates, next_cursor) = db.scan(cursor, count as usize);
+
+    let keys = candidates
+        .into_iter()
+        .filter(|key| db.get(key).is_some_and(|entry| !entry.is_expired(now)))
+        .filter(|key| {
+            pattern
+                .as_ref()
+                .is_none_or(|pattern| glob::matches(pattern.as_bytes(), key.as_bytes()))
+        })
+        .map(|key| Frame::Bulk(key.to_string().into()))
+        .collect();
+
+    Ok(Frame::Array(vec![
+        Frame::Bulk(next_cursor.to_string().into()),
+        Frame::Array(keys),
+    ]))
+}
+
+/// `TYPE key`
+///
+/// キーの値の型を返す。キーが存在しない場合は`none`を返す。
+pub(crate) fn type_(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    parse.finish()?;
+
+    let mut db = cx.db.lock().unwrap();
+    let name = live_entry(&mut db, &key).map_or("none", |entry| entry.value.type_name());
+
+    Ok(Frame::Simple(name.into()))
+}
+
+/// `EXPIRE key seconds`
+///
+/// キーの期限を現在から`seconds`秒後に設定する。期限を設定した場合は1、キーが存在しない場合は0を返す。
+/// `seconds`が0以下の場合はキーを直ちに削除する。
+pub(crate) fn expire(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    let seconds = parse.next_int()?;
+    parse.finish()?;
+
+    let mut db = cx.db.lock().unwrap();
+    let entry = match live_entry(&mut db, &key) {
+        Some(entry) => entry,
+        None => return Ok(Frame::Integer(0)),
+    };
+
+    if seconds <= 0 {
+        db.remove(&key);
+        return Ok(Frame::Integer(1));
+    }
+
+    match deadline_after(Duration::from_secs(seconds as u64)) {
+        Some(expires_at) => {
+            entry.expires_at = Some(expires_at);
+            Ok(Frame::Integer(1))
+        }
+        None => Ok(invalid_expire_time("expire")),
+    }
+}
+
+/// `EXPIREAT key unix-time-seconds`
+///
+/// キーの期限をUNIX時間(秒)で表した時刻に設定する。期限を設定した場合は1、キーが存在しない場合は0を返す。
+/// 過去の時刻を指定した場合はキーを直ちに削除する。
+pub(crate) fn expireat(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    expire_at(cx.db, parse, "expireat", 1000)
+}
+
+/// `PEXPIREAT key unix-time-milliseconds`
+///
+/// キーの期限をUNIX時間(ミリ秒)で表した時刻に設定する。期限を設定した場合は1、キーが存在しない場合は0を返す。
+/// 過去の時刻を指定した場合はキーを直ちに削除する。
+pub(crate) fn pexpireat(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    expire_at(cx.db, parse, "pexpireat", 1)
+}
+
+/// キーの期限を、`millis_per_unit`を掛けてミリ秒に変換したUNIX時間の時刻に設定する。
+///
+/// ミリ秒への変換でオーバーフローする場合は、期限が不正なエラーを返す。
+fn expire_at(
+    db: &Db,
+    parse: &mut Parse,
+    command: &str,
+    millis_per_unit: i64,
+) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    let timestamp = parse.next_int()?;
+    parse.finish()?;
+
+    let millis = match timestamp.checked_mul(millis_per_unit) {
+        Some(millis) => millis,
+        None => return Ok(invalid_expire_time(command)),
+    };
+
+    // 負の時刻はUNIXエポックより前のため、過去の時刻として扱う
+    let expires_at = match UNIX_EPOCH
+        .checked_add(Duration::from_millis(millis.max(0) as u64))
+        .and_then(instant_at)
+    {
+        Some(expires_at) => expires_at,
+        None => return Ok(invalid_expire_time(command)),
+    };
+
+    let mut db = db.lock().unwrap();
+    let entry = match live_entry(&mut db, &key) {
+        Some(entry) => entry,
+        None => return Ok(Frame::Integer(0)),
+    };
+
+    if expires_at <= Instant::now() {
+        db.remove(&key);
+    } else {
+        entry.expires_at = Some(expires_at);
+    }
+
+    Ok(Frame::Integer(1))
+}
+
+/// `PERSIST key`
+///
+/// キーの期限を解除する。期限を解除した場合は1、キーが存在しないか期限が設定されていない場合は0を返す。
+pub(crate) fn persist(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    parse.finish()?;
+
+    let mut db = cx.db.lock().unwrap();
+    let persisted = live_entry(&mut db, &key)
+        .and_then(|entry| entry.expires_at.take())
+        .is_some();
+
+    Ok(Frame::Integer(persisted as i64))
+}
+
+/// `TTL key`
+///
+/// キーが期限切れになるまでの残り時間を秒単位で返す。
+pub(crate) fn ttl(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    // 残り時間はミリ秒から四捨五入して秒に変換する
+    remaining(cx.db, parse, |remaining| {
+        (remaining.as_millis() as i64 + 500) / 1000
+    })
+}
+
+/// `PTTL key`
+///
+/// キーが期限切れになるまでの残り時間をミリ秒単位で返す。
+pub(crate) fn pttl(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    remaining(cx.db, parse, |remaining| remaining.as_millis() as i64)
+}
+
+/// キーが期限切れになるまでの残り時間を`unit`で変換して返す。
+///
+/// キーが存在しない場合は-2、キーに期限が設定されていない場合は-1を返す。
+fn remaining(
+    db: &Db,
+    parse: &mut Parse,
+    unit: impl Fn(Duration) -> i64,
+) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    parse.finish()?;
+
+    let mut db = db.lock().unwrap();
+    let response = match live_entry(&mut db, &key) {
+        None => -2,
+        Some(entry) => match entry.expires_at {
+            None => -1,
+            Some(expires_at) => unit(expires_at.saturating_duration_since(Instant::now())),
+        },
+    };
+
+    Ok(Frame::Integer(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::call;
+    use crate::db::tests::BlockingDrop;
+    use crate::db::{drop_in_background, new_db, Value};
+    use bytes::Bytes;
+    use std::collections::HashMap;
+
+    #[test]
+    fn unlink_releases_lock_before_dropping_value() {
+        // 値を破棄するスレッドを1つにして、破棄を依頼した順に実行させる
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .max_blocking_threads(1)
+            .build()
+            .unwrap();
+        let _guard = runtime.enter();
+
+        // 1フィールドあたり64バイトで、合計数メガバイトのハッシュを作る
+        let hash: HashMap<_, _> = (0..100_000)
+            .map(|i| {
+                (
+                    Bytes::from(format!("field:{}", i)),
+                    Bytes::from(vec![0; 64]),
+                )
+            })
+            .collect();
+        let db = new_db();
+        db.lock()
+            .unwrap()
+            .insert("hash".into(), Entry::new(Value::Hash(hash)));
+
+        // 破棄を止めた値でスレッドを塞ぎ、ハッシュの破棄を待たせる
+        let (blocker, release, _) = BlockingDrop::new();
+        drop_in_background(blocker);
+
+        let response = call(unlink, &db, &["UNLINK", "hash", "missing"]);
+        assert_eq!(Frame::Integer(1), response);
+
+        // ハッシュの破棄が終わる前に、他のコマンドを実行できる
+        let response = call(exists, &db, &["EXISTS", "hash"]);
+        assert_eq!(Frame::Integer(0), response);
+
+        // ハッシュの後に依頼した破棄が終われば、ハッシュの破棄も終わっている
+        release.send(()).unwrap();
+        let (probe, release, dropped) = BlockingDrop::new();
+        release.send(()).unwrap();
+        drop_in_background(probe);
+        dropped.recv_timeout(Duration::from_secs(5)).unwrap();
+    }
+}
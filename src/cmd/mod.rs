@@ -0,0 +1,78 @@
+//! サーバが実行するコマンドの実装
+
+use crate::parse::{Parse, ParseError};
+use crate::{Config, Db, Frame};
+
+/// コマンドの実行時に参照する、サーバが共有する状態
+pub(crate) struct Context<'a> {
+    /// 全てのコネクションで共有するデータベース
+    pub(crate) db: &'a Db,
+
+    /// サーバの設定
+    pub(crate) config: &'a Config,
+}
+
+/// コマンドの実装
+pub(crate) type Handler = fn(&Context, &mut Parse) -> Result<Frame, ParseError>;
+
+/// 引数の組み合わせが不正な場合のエラーメッセージ
+const SYNTAX_ERROR: &str = "ERR syntax error";
+
+/// 操作対象のキーが存在しない場合のエラーメッセージ
+const NO_SUCH_KEY: &str = "ERR no such key";
+
+/// キーの値の型がコマンドに対応していない場合のエラーメッセージ
+const WRONGTYPE: &str = "WRONGTYPE Operation against a key holding the wrong kind of value";
+
+/// 期限が不正な場合のエラーフレームを返す。
+fn invalid_expire_time(command: &str) -> Frame {
+    Frame::Error(format!("ERR invalid expire time in '{}' command", command))
+}
+
+/// クライアントから受け取った文字列を、エラーメッセージに含められるように改行文字を空白に置き換える。
+///
+/// 置き換えないと1つのエラーが複数の応答として送信され、クライアントが後続の応答を読み違える。
+pub(crate) fn printable(arg: &str) -> String {
+    arg.replace(['\r', '\n'], " ")
+}
+
+/// `args`をコマンドとして`handler`で実行して、応答を返す。
+#[cfg(test)]
+fn call(handler: Handler, db: &Db, args: &[&str]) -> Frame {
+    let frame = Frame::Array(
+        args.iter()
+            .map(|arg| Frame::Bulk(bytes::Bytes::copy_from_slice(arg.as_bytes())))
+            .collect(),
+    );
+    let mut parse = Parse::new(frame).unwrap();
+    parse.next_string().unwrap();
+
+    let config = Config::default();
+    let cx = Context {
+        db,
+        config: &config,
+    };
+
+    handler(&cx, &mut parse).unwrap()
+}
+
+mod connection;
+pub(crate) use connection::{echo, ping};
+
+mod hashes;
+pub(crate) use hashes::{hdel, hget, hgetall, hset};
+
+mod keys;
+pub(crate) use keys::{
+    copy, del, exists, expire, expireat, keys, persist, pexpireat, pttl, randomkey, rename,
+    renamenx, scan, touch, ttl, type_, unlink,
+};
+
+mod server;
+pub(crate) use server::{dbsize, debug, flushall, flushdb, object};
+
+mod strings;
+pub(crate) use strings::{
+    append, decr, decrby, get, getdel, getex, getrange, getset, incr, incrby, incrbyfloat, mget,
+    mset, msetnx, set, setnx, setrange, strlen,
+};
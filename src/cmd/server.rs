@@ -0,0 +1,126 @@
+//! サーバの状態を操作または確認するコマンド
+
+use super::{printable, Context, NO_SUCH_KEY};
+use crate::db::{drop_in_background, live_entry};
+use crate::parse::{Parse, ParseError};
+use crate::{Db, Frame};
+use std::sync::atomic::Ordering;
+use tokio::time::Instant;
+
+/// `DBSIZE`
+///
+/// キーの数を返す。期限切れのキーは、まだ削除されていなくても数えない。
+pub(crate) fn dbsize(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    parse.finish()?;
+
+    let now = Instant::now();
+    let db = cx.db.lock().unwrap();
+    let count = db.values().filter(|entry| !entry.is_expired(now)).count();
+
+    Ok(Frame::Integer(count as i64))
+}
+
+/// `FLUSHDB`
+///
+/// 現在のデータベースの全てのキーを削除する。
+pub(crate) fn flushdb(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    flush(cx.db, parse)
+}
+
+/// `FLUSHALL`
+///
+/// 全てのデータベースの全てのキーを削除する。データベースは1つしかないため、`FLUSHDB`と同じ動作をする。
+pub(crate) fn flushall(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    flush(cx.db, parse)
+}
+
+/// 全てのキーを削除する。
+///
+/// ロックを保持している間は空のマップと入れ替えるだけにして、削除したエントリはバックグラウンドで破棄する。
+/// 期限はエントリに保持しているため、エントリと一緒に破棄される。
+fn flush(db: &Db, parse: &mut Parse) -> Result<Frame, ParseError> {
+    parse.finish()?;
+
+    let entries = std::mem::take(&mut *db.lock().unwrap());
+    if !entries.is_empty() {
+        drop_in_background(entries);
+    }
+
+    Ok(Frame::Simple("OK".into()))
+}
+
+/// `OBJECT subcommand key`
+///
+/// キーの値やアクセスの状況を返す。キーが存在しない場合は`Null`を返す。
+/// このコマンドはキーにアクセスした時刻や回数を更新しない。
+///
+/// - `OBJECT IDLETIME key`: キーに最後にアクセスしてから経過した秒数を返す。
+/// - `OBJECT FREQ key`: キーにアクセスした回数を返す。
+/// - `OBJECT ENCODING key`: 値の内部表現の名前を返す。
+/// - `OBJECT CREATEDTIME key`: キーを作成した時刻(UNIX時間の秒)を返す。
+/// - `OBJECT MODTIME key`: 値を最後に変更した時刻(UNIX時間の秒)を返す。
+pub(crate) fn object(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let subcommand = parse.next_string()?.to_lowercase();
+    if !matches!(
+        &subcommand[..],
+        "idletime" | "freq" | "encoding" | "createdtime" | "modtime"
+    ) {
+        return Ok(Frame::Error(format!(
+            "ERR unknown subcommand '{}' for 'object' command",
+            printable(&subcommand)
+        )));
+    }
+
+    let key = parse.next_string()?;
+    parse.finish()?;
+
+    let mut db = cx.db.lock().unwrap();
+    let entry = match live_entry(&mut db, &key) {
+        Some(entry) => entry,
+        None => return Ok(Frame::Null),
+    };
+
+    match &subcommand[..] {
+        "idletime" => Ok(Frame::Integer(entry.idle_time().as_secs() as i64)),
+        "freq" => Ok(Frame::Integer(
+            entry.access_count.load(Ordering::Relaxed) as i64
+        )),
+        "createdtime" => Ok(Frame::Integer(entry.created_at as i64)),
+        "modtime" => Ok(Frame::Integer(entry.updated_at as i64)),
+        _ => Ok(Frame::Bulk(entry.value.encoding().into())),
+    }
+}
+
+/// `DEBUG subcommand [arg ...]`
+///
+/// 運用やデバッグのための情報を返す。
+///
+/// - `DEBUG OBJECT key`: キーの値の内部表現やメタデータを1行の文字列で返す。
+pub(crate) fn debug(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let subcommand = parse.next_string()?.to_lowercase();
+
+    match &subcommand[..] {
+        "object" => {
+            let key = parse.next_string()?;
+            parse.finish()?;
+
+            let mut db = cx.db.lock().unwrap();
+            match live_entry(&mut db, &key) {
+                Some(entry) => Ok(Frame::Simple(format!(
+                    "type:{} encoding:{} idle_seconds:{} access_count:{} created_at:{} updated_at:{}",
+                    entry.value.type_name(),
+                    entry.value.encoding(),
+                    entry.idle_time().as_secs(),
+                    entry.access_count.load(Ordering::Relaxed),
+                    entry.created_at,
+                    entry.updated_at,
+                ))),
+                None => Ok(Frame::Error(NO_SUCH_KEY.into())),
+            }
+        }
+        _ => Ok(Frame::Error(format!(
+            "ERR unknown subcommand '{}' for 'debug' command",
+            printable(&subcommand)
+        ))),
+    }
+}
@@ -0,0 +1,742 @@
+//! 文字列の値を操作するコマンド
+
+use super::{invalid_expire_time, Context, SYNTAX_ERROR, WRONGTYPE};
+use crate::db::{deadline_after, live_entry, set_value, Entry, Slot, Value};
+use crate::parse::{Parse, ParseError};
+use crate::{Db, Frame};
+use bytes::{Bytes, BytesMut};
+use std::ops::Range;
+use tokio::time::{Duration, Instant};
+
+/// 値が整数として解釈できないか、演算結果が範囲外の場合のエラーメッセージ
+const NOT_INTEGER: &str = "ERR value is not an integer or out of range";
+
+/// 値が浮動小数点数として解釈できない場合のエラーメッセージ
+const NOT_FLOAT: &str = "ERR value is not a valid float";
+
+/// `GET key`
+///
+/// キーの値を返す。キーが存在しない場合は`Null`を返す。キーにアクセスした時刻を更新する。
+pub(crate) fn get(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    parse.finish()?;
+
+    let mut db = cx.db.lock().unwrap();
+    let entry = match live_entry(&mut db, &key) {
+        Some(entry) => entry,
+        None => return Ok(Frame::Null),
+    };
+    entry.touch();
+
+    match &entry.value {
+        Value::String(value) => Ok(Frame::Bulk(value.clone())),
+        _ => Ok(Frame::Error(WRONGTYPE.into())),
+    }
+}
+
+/// `MGET key [key ...]`
+///
+/// 指定したキーの値を指定した順番で配列として返す。存在しないキーと、値が文字列でないキーの要素は`Null`になる。
+/// 全てのキーを1回のロックで読み込むため、ある時点の一貫した値を返す。キーにアクセスした時刻を更新する。
+pub(crate) fn mget(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let keys = parse.next_strings()?;
+
+    let mut db = cx.db.lock().unwrap();
+    let values = keys
+        .iter()
+        .map(|key| {
+            let entry = live_entry(&mut db, key)?;
+            entry.touch();
+            match &entry.value {
+                Value::String(value) => Some(value.clone()),
+                _ => None,
+            }
+        })
+        .map(|value| value.map_or(Frame::Null, Frame::Bulk))
+        .collect();
+
+    Ok(Frame::Array(values))
+}
+
+/// `GETEX key [EX seconds | PX milliseconds | PERSIST]`
+///
+/// キーの値を返して、同時にキーの期限を変更する。キーが存在しない場合は`Null`を返す。
+///
+/// - `EX`: キーの期限を現在から`seconds`秒後に設定する。
+/// - `PX`: キーの期限を現在から`milliseconds`ミリ秒後に設定する。
+/// - `PERSIST`: キーの期限を解除する。
+///
+/// オプションを指定しない場合は`GET`と同じで、期限を変更しない。
+pub(crate) fn getex(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+
+    // `Some(None)`は期限の解除を表す
+    let mut expires_at = None;
+    while parse.has_remaining() {
+        let option = parse.next_string()?.to_uppercase();
+        match &option[..] {
+            "EX" | "PX" if expires_at.is_none() => match next_expiry(parse, &option)? {
+                Some(deadline) => expires_at = Some(Some(deadline)),
+                None => return Ok(invalid_expire_time("getex")),
+            },
+            "PERSIST" if expires_at.is_none() => expires_at = Some(None),
+            _ => return Ok(Frame::Error(SYNTAX_ERROR.into())),
+        }
+    }
+
+    let mut db = cx.db.lock().unwrap();
+    match live_entry(&mut db, &key) {
+        Some(entry) => {
+            let value = match &entry.value {
+                Value::String(value) => value.clone(),
+                _ => return Ok(Frame::Error(WRONGTYPE.into())),
+            };
+            if let Some(expires_at) = expires_at {
+                entry.expires_at = expires_at;
+            }
+            Ok(Frame::Bulk(value))
+        }
+        None => Ok(Frame::Null),
+    }
+}
+
+/// `GETDEL key`
+///
+/// キーの値を返してキーを削除する。キーが存在しない場合は`Null`を返す。
+/// 読み込みと削除を1回のロックで行うため、同時に実行しても値を受け取るのは1つだけである。
+pub(crate) fn getdel(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    parse.finish()?;
+
+    let mut db = cx.db.lock().unwrap();
+    match live_entry(&mut db, &key) {
+        Some(Entry {
+            value: Value::String(value),
+            ..
+        }) => {
+            let value = value.clone();
+            db.remove(&key);
+            Ok(Frame::Bulk(value))
+        }
+        Some(_) => Ok(Frame::Error(WRONGTYPE.into())),
+        None => Ok(Frame::Null),
+    }
+}
+
+/// `SET key value [NX | XX] [EX seconds | PX milliseconds]`
+///
+/// キーに値を設定する。期限を指定しない場合、キーに設定されていた期限は解除される。
+///
+/// - `NX`: キーが存在しない場合に限り設定する。
+/// - `XX`: キーが存在する場合に限り設定する。
+/// - `EX`: キーの期限を現在から`seconds`秒後に設定する。
+/// - `PX`: キーの期限を現在から`milliseconds`ミリ秒後に設定する。
+///
+/// 条件を満たさず設定しなかった場合は`Null`を返す。
+pub(crate) fn set(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    let value = parse.next_bytes()?;
+
+    let mut condition = None;
+    let mut expires_at = None;
+    while parse.has_remaining() {
+        let option = parse.next_string()?.to_uppercase();
+        match &option[..] {
+            "NX" if condition != Some(Condition::Exists) => condition = Some(Condition::Absent),
+            "XX" if condition != Some(Condition::Absent) => condition = Some(Condition::Exists),
+            "EX" | "PX" if expires_at.is_none() => match next_expiry(parse, &option)? {
+                Some(deadline) => expires_at = Some(deadline),
+                None => return Ok(invalid_expire_time("set")),
+            },
+            _ => return Ok(Frame::Error(SYNTAX_ERROR.into())),
+        }
+    }
+
+    let mut db = cx.db.lock().unwrap();
+    let exists = live_entry(&mut db, &key).is_some();
+    match condition {
+        Some(Condition::Absent) if exists => return Ok(Frame::Null),
+        Some(Condition::Exists) if !exists => return Ok(Frame::Null),
+        _ => {}
+    }
+    set_value(&mut db, key, value, expires_at);
+
+    Ok(Frame::Simple("OK".to_string()))
+}
+
+/// `SET`で値を設定する条件
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Condition {
+    /// キーが存在しない(`NX`)
+    Absent,
+
+    /// キーが存在する(`XX`)
+    Exists,
+}
+
+/// `MSET key value [key value ...]`
+///
+/// 全てのキーに値を1回のロックで設定する。`SET`と同様に、キーに設定されていた期限は解除される。
+/// 引数の数が不正な場合は、どのキーも設定しない。
+pub(crate) fn mset(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let pairs = next_pairs(parse)?;
+
+    let mut db = cx.db.lock().unwrap();
+    for (key, value) in pairs {
+        set_value(&mut db, key, value, None);
+    }
+
+    Ok(Frame::Simple("OK".to_string()))
+}
+
+/// `MSETNX key value [key value ...]`
+///
+/// どのキーも存在しない場合に限り、全てのキーに値を設定する。
+/// 設定した場合は1、いずれかのキーが存在したため何も設定しなかった場合は0を返す。
+/// 存在の確認と設定を1回のロックで行うため、一部のキーだけが設定されることはない。
+pub(crate) fn msetnx(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let pairs = next_pairs(parse)?;
+
+    let mut db = cx.db.lock().unwrap();
+    if pairs
+        .iter()
+        .any(|(key, _)| live_entry(&mut db, key).is_some())
+    {
+        return Ok(Frame::Integer(0));
+    }
+
+    for (key, value) in pairs {
+        set_value(&mut db, key, value, None);
+    }
+
+    Ok(Frame::Integer(1))
+}
+
+/// `SETNX key value`
+///
+/// キーが存在しない場合に限り値を設定する。設定した場合は1、キーが既に存在する場合は0を返す。
+pub(crate) fn setnx(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    let value = parse.next_bytes()?;
+    parse.finish()?;
+
+    // 存在の確認と挿入を`entry`で一度に行い、ロックを解放するまでに他の書き込みを挟まない
+    let now = Instant::now();
+    let mut db = cx.db.lock().unwrap();
+    let inserted = match db.entry(key) {
+        Slot::Occupied(mut slot) if slot.get().is_expired(now) => {
+            slot.insert(Entry::new(Value::String(value)));
+            true
+        }
+        Slot::Occupied(_) => false,
+        Slot::Vacant(slot) => {
+            slot.insert(Entry::new(Value::String(value)));
+            true
+        }
+    };
+
+    Ok(Frame::Integer(inserted as i64))
+}
+
+/// `GETSET key value`
+///
+/// キーに値を設定して、設定前の値を返す。キーが存在しなかった場合は`Null`を返す。
+///
+/// `SET`と同様に、キーに設定されていた期限は解除する。
+/// 読み込みと書き込みを1回のロックで行うため、同時に実行された`SET`を失わない。
+pub(crate) fn getset(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    let value = parse.next_bytes()?;
+    parse.finish()?;
+
+    let mut db = cx.db.lock().unwrap();
+    let previous = match live_entry(&mut db, &key) {
+        Some(Entry {
+            value: Value::String(value),
+            ..
+        }) => Some(value.clone()),
+        Some(_) => return Ok(Frame::Error(WRONGTYPE.into())),
+        None => None,
+    };
+    set_value(&mut db, key, value, None);
+
+    Ok(previous.map_or(Frame::Null, Frame::Bulk))
+}
+
+/// `APPEND key value`
+///
+/// キーの値の末尾に`value`を追加して、追加後の値の長さを返す。
+/// キーが存在しない場合は`SET`と同様に値を設定する。キーに設定されていた期限は維持する。
+pub(crate) fn append(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    let value = parse.next_bytes()?;
+    parse.finish()?;
+
+    let mut db = cx.db.lock().unwrap();
+    let len = match live_entry(&mut db, &key) {
+        Some(entry) => {
+            let current = match &entry.value {
+                Value::String(current) => current,
+                _ => return Ok(Frame::Error(WRONGTYPE.into())),
+            };
+
+            // `Bytes`は変更できないため、複製して連結する
+            let mut appended = BytesMut::with_capacity(current.len() + value.len());
+            appended.extend_from_slice(current);
+            appended.extend_from_slice(&value);
+            let len = appended.len();
+            entry.update(Value::String(appended.freeze()));
+            len
+        }
+        None => {
+            let len = value.len();
+            db.insert(key, Entry::new(Value::String(value)));
+            len
+        }
+    };
+
+    Ok(Frame::Integer(len as i64))
+}
+
+/// `GETRANGE key start end`
+///
+/// キーの値の`start`バイト目から`end`バイト目まで(両端を含む)を返す。
+/// 負の添字は末尾からの位置として扱う。範囲が空の場合やキーが存在しない場合は空の値を返す。
+pub(crate) fn getrange(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    let start = parse.next_int()?;
+    let end = parse.next_int()?;
+    parse.finish()?;
+
+    let mut db = cx.db.lock().unwrap();
+    let value = match live_entry(&mut db, &key) {
+        Some(Entry {
+            value: Value::String(value),
+            ..
+        }) => value,
+        Some(_) => return Ok(Frame::Error(WRONGTYPE.into())),
+        None => return Ok(Frame::Bulk(Bytes::new())),
+    };
+
+    // `Bytes::slice`は値を複製せずに参照を共有する
+    match normalize_range(start, end, value.len()) {
+        Some(range) => Ok(Frame::Bulk(value.slice(range))),
+        None => Ok(Frame::Bulk(Bytes::new())),
+    }
+}
+
+/// `SETRANGE key offset value`
+///
+/// キーの値の`offset`バイト目から`value`を上書きして、上書き後の値の長さを返す。
+/// 値が`offset`より短い場合は0で埋める。キーが存在しない場合は空の値として扱い、キーに設定されていた期限は維持する。
+/// 上書き後の値の長さが設定の`max_value_len`を超える場合はエラーを返す。
+pub(crate) fn setrange(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    let offset = parse.next_int()?;
+    let value = parse.next_bytes()?;
+    parse.finish()?;
+
+    if offset < 0 {
+        return Ok(Frame::Error("ERR offset is out of range".into()));
+    }
+    let offset = offset as usize;
+    if !value.is_empty() && cx.config.max_value_len < offset.saturating_add(value.len()) {
+        return Ok(Frame::Error(
+            "ERR string exceeds maximum allowed size".into(),
+        ));
+    }
+
+    let mut db = cx.db.lock().unwrap();
+    let entry = live_entry(&mut db, &key);
+
+    let current = match &entry {
+        Some(Entry {
+            value: Value::String(current),
+            ..
+        }) => current.clone(),
+        Some(_) => return Ok(Frame::Error(WRONGTYPE.into())),
+        None => Bytes::new(),
+    };
+
+    // 空の値を書き込む場合は、キーを作成も変更もしない
+    if value.is_empty() {
+        return Ok(Frame::Integer(current.len() as i64));
+    }
+
+    // `Bytes`は変更できないため、複製して必要な長さまで0で埋めてから書き込む
+    let end = offset + value.len();
+    let mut updated = BytesMut::from(&current[..]);
+    if updated.len() < end {
+        updated.resize(end, 0);
+    }
+    updated[offset..end].copy_from_slice(&value);
+    let len = updated.len();
+
+    match entry {
+        Some(entry) => entry.update(Value::String(updated.freeze())),
+        None => {
+            db.insert(key, Entry::new(Value::String(updated.freeze())));
+        }
+    }
+
+    Ok(Frame::Integer(len as i64))
+}
+
+/// `STRLEN key`
+///
+/// キーの値のバイト数を返す。キーが存在しない場合は0を返す。
+pub(crate) fn strlen(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    parse.finish()?;
+
+    let mut db = cx.db.lock().unwrap();
+    let len = match live_entry(&mut db, &key) {
+        Some(Entry {
+            value: Value::String(value),
+            ..
+        }) => value.len(),
+        Some(_) => return Ok(Frame::Error(WRONGTYPE.into())),
+        None => 0,
+    };
+
+    Ok(Frame::Integer(len as i64))
+}
+
+/// `INCR key`
+///
+/// キーの値を1加算して、加算後の値を返す。
+pub(crate) fn incr(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    parse.finish()?;
+
+    Ok(update_integer(cx.db, key, |current| current.checked_add(1)))
+}
+
+/// `DECR key`
+///
+/// キーの値を1減算して、減算後の値を返す。
+pub(crate) fn decr(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    parse.finish()?;
+
+    Ok(update_integer(cx.db, key, |current| current.checked_sub(1)))
+}
+
+/// `INCRBY key delta`
+///
+/// キーの値に`delta`を加算して、加算後の値を返す。
+pub(crate) fn incrby(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    let delta = parse.next_int()?;
+    parse.finish()?;
+
+    Ok(update_integer(cx.db, key, |current| {
+        current.checked_add(delta)
+    }))
+}
+
+/// `DECRBY key delta`
+///
+/// キーの値から`delta`を減算して、減算後の値を返す。
+pub(crate) fn decrby(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    let delta = parse.next_int()?;
+    parse.finish()?;
+
+    Ok(update_integer(cx.db, key, |current| {
+        current.checked_sub(delta)
+    }))
+}
+
+/// `INCRBYFLOAT key delta`
+///
+/// キーの値を浮動小数点数として解釈して`delta`を加算し、加算後の値を返す。
+/// キーが存在しない場合は0として扱い、キーに設定されていた期限は維持する。
+pub(crate) fn incrbyfloat(cx: &Context, parse: &mut Parse) -> Result<Frame, ParseError> {
+    let key = parse.next_string()?;
+    let delta = parse.next_bytes()?;
+    parse.finish()?;
+
+    let delta = match parse_float(&delta) {
+        Some(delta) => delta,
+        None => return Ok(Frame::Error(NOT_FLOAT.into())),
+    };
+
+    let mut db = cx.db.lock().unwrap();
+    let entry = live_entry(&mut db, &key);
+
+    let current = match &entry {
+        Some(Entry {
+            value: Value::String(value),
+            ..
+        }) => match parse_float(value) {
+            Some(current) => current,
+            None => return Ok(Frame::Error(NOT_FLOAT.into())),
+        },
+        Some(_) => return Ok(Frame::Error(WRONGTYPE.into())),
+        None => 0.0,
+    };
+    let value = current + delta;
+    if !value.is_finite() {
+        return Ok(Frame::Error(
+            "ERR increment would produce NaN or Infinity".into(),
+        ));
+    }
+
+    let formatted = Bytes::from(format_float(value));
+    match entry {
+        Some(entry) => entry.update(Value::String(formatted.clone())),
+        None => {
+            db.insert(key, Entry::new(Value::String(formatted.clone())));
+        }
+    }
+
+    Ok(Frame::Bulk(formatted))
+}
+
+/// キーの値を整数として解釈して`op`を適用し、適用後の値を返す。
+///
+/// キーが存在しない場合は0として扱う。`op`が`None`を返した場合はオーバーフローとして扱う。
+/// キーに設定されていた期限は維持する。
+/// 読み込みから書き込みまで1回のロックで行うため、同時に実行しても更新を失わない。
+fn update_integer(db: &Db, key: String, op: impl FnOnce(i64) -> Option<i64>) -> Frame {
+    let mut db = db.lock().unwrap();
+    let entry = live_entry(&mut db, &key);
+
+    let current = match &entry {
+        Some(Entry {
+            value: Value::String(value),
+            ..
+        }) => match parse_integer(value) {
+            Some(current) => current,
+            None => return Frame::Error(NOT_INTEGER.into()),
+        },
+        Some(_) => return Frame::Error(WRONGTYPE.into()),
+        None => 0,
+    };
+    let value = match op(current) {
+        Some(value) => value,
+        None => return Frame::Error(NOT_INTEGER.into()),
+    };
+
+    let formatted = format_integer(value);
+    match entry {
+        Some(entry) => entry.update(Value::String(formatted)),
+        None => {
+            db.insert(key, Entry::new(Value::String(formatted)));
+        }
+    }
+
+    Frame::Integer(value)
+}
+
+/// 残りの引数をキーと値の組として全て読み込む。組は1つ以上必要である。
+fn next_pairs(parse: &mut Parse) -> Result<Vec<(String, Bytes)>, ParseError> {
+    let mut pairs = vec![(parse.next_string()?, parse.next_bytes()?)];
+    while parse.has_remaining() {
+        pairs.push((parse.next_string()?, parse.next_bytes()?));
+    }
+
+    Ok(pairs)
+}
+
+/// `EX`または`PX`オプションの値を読み込み、期限となる時刻を返す。
+///
+/// `option`が`EX`の場合は秒、`PX`の場合はミリ秒として解釈する。
+/// 値が0以下、または時刻が表現できない程大きい場合は`None`を返す。
+fn next_expiry(parse: &mut Parse, option: &str) -> Result<Option<Instant>, ParseError> {
+    let amount = parse.next_int()?;
+    if amount <= 0 {
+        return Ok(None);
+    }
+
+    let duration = match option {
+        "EX" => Duration::from_secs(amount as u64),
+        _ => Duration::from_millis(amount as u64),
+    };
+
+    Ok(deadline_after(duration))
+}
+
+/// `GETRANGE`の`start`と`end`(両端を含む)を、長さ`len`の値に対する添字の範囲に変換する。
+///
+/// 負の添字は末尾からの位置として扱い、値の範囲外の添字は値の両端に丸める。
+/// 範囲が空になる場合は`None`を返す。
+fn normalize_range(start: i64, end: i64, len: usize) -> Option<Range<usize>> {
+    if start < 0 && end < 0 && start > end {
+        return None;
+    }
+
+    let len = len as i64;
+    let start = if start < 0 { len + start } else { start }.max(0);
+    let end = if end < 0 { len + end } else { end }.max(0).min(len - 1);
+    if len == 0 || start > end {
+        return None;
+    }
+
+    Some(start as usize..end as usize + 1)
+}
+
+/// ASCIIで表現された符号付き64ビット整数を解析する。
+fn parse_integer(value: &[u8]) -> Option<i64> {
+    std::str::from_utf8(value).ok()?.parse().ok()
+}
+
+/// 整数をASCIIで表現したバイト列に変換する。
+fn format_integer(value: i64) -> Bytes {
+    Bytes::from(value.to_string())
+}
+
+/// ASCIIで表現された有限の浮動小数点数を解析する。
+///
+/// `inf`や`nan`のような有限でない値は受け付けない。
+fn parse_float(value: &[u8]) -> Option<f64> {
+    let value: f64 = std::str::from_utf8(value).ok()?.parse().ok()?;
+
+    value.is_finite().then_some(value)
+}
+
+/// 浮動小数点数を正規化した文字列に変換する。
+///
+/// 末尾の0や小数点は付けず、指数表記も使用しない。また、-0は0に変換する。
+fn format_float(value: f64) -> String {
+    if value == 0.0 {
+        return "0".to_string();
+    }
+
+    // `f64`の`Display`は、値を復元できる最短の桁数で指数表記を使わずに出力する
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::call;
+    use crate::db::{new_db, unix_time_millis};
+    use std::collections::HashMap;
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    fn string_commands_reject_other_types() {
+        let db = new_db();
+        let hash = HashMap::from([(Bytes::from("field"), Bytes::from("1"))]);
+        db.lock()
+            .unwrap()
+            .insert("hash".into(), Entry::new(Value::Hash(hash.clone())));
+
+        let wrong_type = Frame::Error(WRONGTYPE.into());
+        assert_eq!(wrong_type, call(get, &db, &["GET", "hash"]));
+        assert_eq!(wrong_type, call(getex, &db, &["GETEX", "hash", "PERSIST"]));
+        assert_eq!(wrong_type, call(getdel, &db, &["GETDEL", "hash"]));
+        assert_eq!(wrong_type, call(getset, &db, &["GETSET", "hash", "v"]));
+        assert_eq!(wrong_type, call(append, &db, &["APPEND", "hash", "v"]));
+        assert_eq!(wrong_type, call(strlen, &db, &["STRLEN", "hash"]));
+        assert_eq!(wrong_type, call(incr, &db, &["INCR", "hash"]));
+        assert_eq!(
+            wrong_type,
+            call(incrbyfloat, &db, &["INCRBYFLOAT", "hash", "1"])
+        );
+        assert_eq!(
+            Frame::Array(vec![Frame::Null]),
+            call(mget, &db, &["MGET", "hash"])
+        );
+
+        // 失敗したコマンドは値を変更しない
+        assert_eq!(
+            Value::Hash(hash),
+            db.lock().unwrap().get("hash").unwrap().value
+        );
+
+        // `SET`は値の型によらず上書きする
+        assert_eq!(
+            Frame::Simple("OK".into()),
+            call(set, &db, &["SET", "hash", "v"])
+        );
+        assert_eq!(Frame::Bulk("v".into()), call(get, &db, &["GET", "hash"]));
+    }
+
+    #[test]
+    fn get_and_mget_reset_idle_time() {
+        let db = new_db();
+        for key in ["foo", "bar"] {
+            let entry = Entry::new(Value::String("value".into()));
+            entry
+                .last_accessed
+                .store(unix_time_millis() - 10_000, Ordering::Relaxed);
+            db.lock().unwrap().insert(key.into(), entry);
+        }
+        let idle_time = |key: &str| db.lock().unwrap().get(key).unwrap().idle_time();
+        assert!(Duration::from_secs(10) <= idle_time("foo"));
+
+        call(get, &db, &["GET", "foo"]);
+        assert!(idle_time("foo") < Duration::from_secs(1));
+
+        assert!(Duration::from_secs(10) <= idle_time("bar"));
+        call(mget, &db, &["MGET", "bar"]);
+        assert!(idle_time("bar") < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn normalize_range_with_positive_indexes() {
+        assert_eq!(Some(0..4), normalize_range(0, 3, 10));
+        assert_eq!(Some(0..10), normalize_range(0, 9, 10));
+        assert_eq!(Some(5..6), normalize_range(5, 5, 10));
+    }
+
+    #[test]
+    fn normalize_range_with_negative_indexes() {
+        assert_eq!(Some(0..10), normalize_range(0, -1, 10));
+        assert_eq!(Some(7..10), normalize_range(-3, -1, 10));
+        assert_eq!(Some(9..10), normalize_range(-1, -1, 10));
+        assert_eq!(Some(2..9), normalize_range(2, -2, 10));
+    }
+
+    #[test]
+    fn normalize_range_clamps_out_of_range_indexes() {
+        assert_eq!(Some(0..10), normalize_range(-100, 100, 10));
+        assert_eq!(Some(0..1), normalize_range(0, -100, 10));
+        assert_eq!(Some(5..10), normalize_range(5, i64::MAX, 10));
+    }
+
+    #[test]
+    fn normalize_range_returns_none_for_empty_ranges() {
+        assert_eq!(None, normalize_range(5, 3, 10));
+        assert_eq!(None, normalize_range(10, 20, 10));
+        assert_eq!(None, normalize_range(-1, -3, 10));
+        assert_eq!(None, normalize_range(0, -1, 0));
+        assert_eq!(None, normalize_range(0, 0, 0));
+    }
+
+    #[test]
+    fn format_float_drops_trailing_zeros() {
+        assert_eq!("10", format_float(10.0));
+        assert_eq!("10.5", format_float(10.50));
+        assert_eq!("-3", format_float(-3.0));
+    }
+
+    #[test]
+    fn format_float_does_not_use_exponent() {
+        assert_eq!("0.0001", format_float(1e-4));
+        assert_eq!("5000000000000000000000", format_float(5e21));
+    }
+
+    #[test]
+    fn format_float_normalizes_negative_zero() {
+        assert_eq!("0", format_float(-0.0));
+    }
+
+    #[test]
+    fn format_float_round_trips() {
+        let value = 0.1 + 0.2;
+
+        assert_eq!(Some(value), parse_float(format_float(value).as_bytes()));
+    }
+
+    #[test]
+    fn parse_float_rejects_non_finite_values() {
+        assert_eq!(None, parse_float(b"inf"));
+        assert_eq!(None, parse_float(b"NaN"));
+        assert_eq!(None, parse_float(b"1.5x"));
+        assert_eq!(Some(-1.5), parse_float(b"-1.5"));
+    }
+}
@@ -0,0 +1,92 @@
+//! TCPストリームとフレームを相互に変換するコネクション
+
+use crate::frame::{self, Frame};
+use bytes::{Buf, BytesMut};
+use std::io::{self, Cursor};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
+use tokio::net::TcpStream;
+
+/// 読み込み用のバッファの大きさの上限(Redisの`client-query-buffer-limit`の既定値)
+///
+/// 1つのフレームを受信し終えるまでにバッファがこの大きさを超えた場合は、エラーにする。
+const MAX_BUFFER_LEN: usize = 1024 * 1024 * 1024;
+
+/// リモートピアとフレームを送受信する。
+#[derive(Debug)]
+pub struct Connection {
+    // 書き込みをバッファリングするストリーム
+    stream: BufWriter<TcpStream>,
+
+    // フレームを読み込むためのバッファ
+    buffer: BytesMut,
+}
+
+impl Connection {
+    /// `socket`で送受信する`Connection`を作成する。
+    pub fn new(socket: TcpStream) -> Connection {
+        Connection {
+            stream: BufWriter::new(socket),
+            buffer: BytesMut::with_capacity(4 * 1024),
+        }
+    }
+
+    /// ストリームから1つのフレームを読み込む。
+    ///
+    /// フレームの途中以外でリモートがコネクションを閉じた場合は`None`を返す。
+    pub async fn read_frame(&mut self) -> crate::Result<Option<Frame>> {
+        loop {
+            // バッファにフレーム全体があれば、それを返す
+            if let Some(frame) = self.parse_frame()? {
+                return Ok(Some(frame));
+            }
+
+            // 上限まで受信してもフレームが完成しない場合は、それ以上バッファを大きくしない
+            if self.buffer.len() >= MAX_BUFFER_LEN {
+                return Err("protocol error; query buffer limit exceeded".into());
+            }
+
+            // 0バイトはストリームの終端を示す
+            if 0 == self.stream.read_buf(&mut self.buffer).await? {
+                // バッファにデータが残っている場合は、フレームの途中で閉じられた
+                if self.buffer.is_empty() {
+                    return Ok(None);
+                } else {
+                    return Err("connection reset by peer".into());
+                }
+            }
+        }
+    }
+
+    /// バッファからフレームを解析する。
+    ///
+    /// データが不足している場合は`None`を返す。
+    fn parse_frame(&mut self) -> crate::Result<Option<Frame>> {
+        let mut buf = Cursor::new(&self.buffer[..]);
+
+        match Frame::check(&mut buf) {
+            Ok(_) => {
+                // `check`はフレームの終端までカーソルを進める
+                let len = buf.position() as usize;
+
+                buf.set_position(0);
+                let frame = Frame::parse(&mut buf)?;
+
+                // 解析したデータをバッファから取り除く
+                self.buffer.advance(len);
+
+                Ok(Some(frame))
+            }
+            Err(frame::Error::Incomplete) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// ストリームに1つのフレームを書き込む。
+    pub async fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        let mut buf = BytesMut::new();
+        frame.encode(&mut buf);
+
+        self.stream.write_all(&buf).await?;
+        self.stream.flush().await
+    }
+}
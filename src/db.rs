@@ -0,0 +1,626 @@
+//! サーバが保持するキーと値の共有ストア
+
+use bytes::Bytes;
+use std::collections::hash_map::{self, DefaultHasher, HashMap};
+use std::collections::BTreeSet;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::oneshot;
+use tokio::time::{self, Duration, Instant};
+
+/// 全てのコネクションで共有するデータベース
+pub type Db = Arc<Mutex<Entries>>;
+
+/// キーとエントリの対応
+///
+/// `SCAN`のカーソルのために、キーをハッシュ値の順に並べた索引をエントリと同時に更新する。
+#[derive(Debug, Default)]
+pub struct Entries {
+    /// キーとエントリの対応
+    map: HashMap<String, Entry>,
+
+    /// `scan_hash`とキーの組を順に並べた索引
+    scan_index: BTreeSet<(u64, String)>,
+}
+
+impl Entries {
+    /// キーのエントリを返す。
+    pub fn get(&self, key: &str) -> Option<&Entry> {
+        self.map.get(key)
+    }
+
+    /// キーのエントリを変更可能な参照で返す。
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Entry> {
+        self.map.get_mut(key)
+    }
+
+    /// キーが存在するか確認する。期限切れのエントリも存在するものとして扱う。
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// キーにエントリを設定して、設定前のエントリを返す。
+    pub fn insert(&mut self, key: String, entry: Entry) -> Option<Entry> {
+        if !self.map.contains_key(&key) {
+            self.scan_index.insert((scan_hash(&key), key.clone()));
+        }
+
+        self.map.insert(key, entry)
+    }
+
+    /// キーに対応する場所を返す。
+    ///
+    /// `HashMap::entry`と同様に、キーの存在の確認と挿入を1回の検索で行う。
+    pub fn entry(&mut self, key: String) -> Slot<'_> {
+        match self.map.entry(key) {
+            hash_map::Entry::Occupied(entry) => Slot::Occupied(OccupiedSlot { entry }),
+            hash_map::Entry::Vacant(entry) => Slot::Vacant(VacantSlot {
+                entry,
+                scan_index: &mut self.scan_index,
+            }),
+        }
+    }
+
+    /// キーのエントリを削除して返す。
+    pub fn remove(&mut self, key: &str) -> Option<Entry> {
+        let entry = self.map.remove(key)?;
+        self.scan_index.remove(&(scan_hash(key), key.to_string()));
+
+        Some(entry)
+    }
+
+    /// `keep`が`false`を返したエントリを全て削除する。
+    pub fn retain(&mut self, mut keep: impl FnMut(&String, &Entry) -> bool) {
+        let scan_index = &mut self.scan_index;
+        self.map.retain(|key, entry| {
+            let kept = keep(key, entry);
+            if !kept {
+                scan_index.remove(&(scan_hash(key), key.clone()));
+            }
+            kept
+        });
+    }
+
+    /// 全てのキーとエントリを返す。順序は決まっていない。
+    pub fn iter(&self) -> hash_map::Iter<'_, String, Entry> {
+        self.map.iter()
+    }
+
+    /// 全てのエントリを返す。順序は決まっていない。
+    pub fn values(&self) -> hash_map::Values<'_, String, Entry> {
+        self.map.values()
+    }
+
+    /// エントリの数を返す。期限切れのエントリも数える。
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// エントリが1つもないか確認する。
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// ハッシュ値が`cursor`以上のキーを、ハッシュ値の順に`count`個返す。次のカーソルも一緒に返す。
+    ///
+    /// ハッシュ値が衝突したキーは、`count`を超えても同じ呼び出しで返す。
+    /// 次のカーソルが0の場合は、全てのキーを返し終えたことを表す。
+    /// 索引を範囲で辿るため、費用はエントリの総数ではなく`count`に比例する。
+    pub fn scan(&self, cursor: u64, count: usize) -> (Vec<&str>, u64) {
+        let mut keys = vec![];
+        let mut last_hash = None;
+        for (hash, key) in self.scan_index.range((cursor, String::new())..) {
+            if count <= keys.len() && last_hash != Some(*hash) {
+                return (keys, *hash);
+            }
+            keys.push(key.as_str());
+            last_hash = Some(*hash);
+        }
+
+        (keys, 0)
+    }
+}
+
+/// `Entries::entry`が返す、キーに対応する場所
+pub enum Slot<'a> {
+    /// エントリが存在する場所
+    Occupied(OccupiedSlot<'a>),
+
+    /// エントリが存在しない場所
+    Vacant(VacantSlot<'a>),
+}
+
+/// エントリが存在する場所
+///
+/// キーは変わらないため、`SCAN`の索引を更新する操作はない。
+pub struct OccupiedSlot<'a> {
+    entry: hash_map::OccupiedEntry<'a, String, Entry>,
+}
+
+impl<'a> OccupiedSlot<'a> {
+    /// エントリを返す。
+    pub fn get(&self) -> &Entry {
+        self.entry.get()
+    }
+
+    /// エントリを変更可能な参照で返す。
+    pub fn get_mut(&mut self) -> &mut Entry {
+        self.entry.get_mut()
+    }
+
+    /// エントリを置き換えて、置き換える前のエントリを返す。
+    pub fn insert(&mut self, entry: Entry) -> Entry {
+        self.entry.insert(entry)
+    }
+}
+
+/// エントリが存在しない場所
+pub struct VacantSlot<'a> {
+    entry: hash_map::VacantEntry<'a, String, Entry>,
+    scan_index: &'a mut BTreeSet<(u64, String)>,
+}
+
+impl<'a> VacantSlot<'a> {
+    /// エントリを挿入して、`SCAN`の索引にキーを追加する。
+    pub fn insert(self, entry: Entry) -> &'a mut Entry {
+        let key = self.entry.key();
+        self.scan_index.insert((scan_hash(key), key.clone()));
+
+        self.entry.insert(entry)
+    }
+}
+
+/// `SCAN`でキーを調べる順序を決めるハッシュ値を返す。
+///
+/// 固定の鍵でハッシュ化するため、同じキーに対して常に同じ値を返す。
+fn scan_hash(key: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// データベースに格納する値とそのメタデータ
+///
+/// 期限や時刻などのメタデータは、値の型によらず共通に持つ。
+#[derive(Debug)]
+pub struct Entry {
+    /// キーに格納された値
+    pub value: Value,
+
+    /// キーが期限切れになる時刻
+    ///
+    /// `None`の場合は期限切れにならない。
+    pub expires_at: Option<Instant>,
+
+    /// キーを作成した時刻(UNIX時間の秒)
+    pub created_at: u64,
+
+    /// 値を最後に変更した時刻(UNIX時間の秒)
+    pub updated_at: u64,
+
+    /// キーに最後にアクセスした時刻(UNIX時間のミリ秒)
+    ///
+    /// 読み込みでも更新するため、エントリを変更可能に借用しなくても更新できるようにする。
+    pub last_accessed: AtomicU64,
+
+    /// キーにアクセスした回数
+    pub access_count: AtomicU64,
+}
+
+/// キーに格納する値
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// 文字列
+    String(Bytes),
+
+    /// フィールドと値の組を保持するハッシュ
+    Hash(HashMap<Bytes, Bytes>),
+}
+
+impl Value {
+    /// `TYPE`で返す値の型の名前を返す。
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Hash(_) => "hash",
+        }
+    }
+
+    /// `OBJECT ENCODING`で返す値の内部表現の名前を返す。
+    ///
+    /// 文字列は、整数として解釈できる場合は`int`、それ以外は`raw`とする。
+    pub fn encoding(&self) -> &'static str {
+        match self {
+            Value::String(value) => {
+                let is_integer =
+                    std::str::from_utf8(value).is_ok_and(|value| value.parse::<i64>().is_ok());
+                if is_integer {
+                    "int"
+                } else {
+                    "raw"
+                }
+            }
+            Value::Hash(_) => "hashtable",
+        }
+    }
+}
+
+impl Entry {
+    /// 期限を持たないエントリを作成する。
+    pub fn new(value: Value) -> Entry {
+        let now = unix_time();
+
+        Entry {
+            value,
+            expires_at: None,
+            created_at: now,
+            updated_at: now,
+            last_accessed: AtomicU64::new(unix_time_millis()),
+            access_count: AtomicU64::new(0),
+        }
+    }
+
+    /// 値を変更して、変更時刻を更新する。期限は維持する。
+    pub fn update(&mut self, value: Value) {
+        self.value = value;
+        self.mark_updated();
+    }
+
+    /// 値をその場で変更した後に、変更時刻を更新する。
+    pub fn mark_updated(&mut self) {
+        self.updated_at = unix_time();
+    }
+
+    /// キーにアクセスした時刻を現在の時刻に更新して、アクセスした回数を数える。
+    pub fn touch(&self) {
+        self.last_accessed
+            .store(unix_time_millis(), Ordering::Relaxed);
+        self.access_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// キーに最後にアクセスしてから経過した時間を返す。
+    pub fn idle_time(&self) -> Duration {
+        let last_accessed = self.last_accessed.load(Ordering::Relaxed);
+
+        Duration::from_millis(unix_time_millis().saturating_sub(last_accessed))
+    }
+
+    /// `now`の時点で期限切れになっているか確認する。
+    pub fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// 空のデータベースを作成する。
+pub fn new_db() -> Db {
+    Arc::new(Mutex::new(Entries::default()))
+}
+
+/// 期限切れでないエントリを返す。
+///
+/// エントリが期限切れの場合は、バックグラウンドタスクによる削除を待たずにここで削除する。
+pub(crate) fn live_entry<'a>(entries: &'a mut Entries, key: &str) -> Option<&'a mut Entry> {
+    let now = Instant::now();
+    if entries.get(key).is_some_and(|entry| entry.is_expired(now)) {
+        entries.remove(key);
+    }
+
+    entries.get_mut(key)
+}
+
+/// キーに文字列の値と期限を設定する。
+///
+/// キーが既に存在する場合は、値の型によらず、作成時刻を維持して値と期限を置き換える。
+pub(crate) fn set_value(
+    entries: &mut Entries,
+    key: String,
+    value: Bytes,
+    expires_at: Option<Instant>,
+) {
+    match live_entry(entries, &key) {
+        Some(entry) => {
+            entry.update(Value::String(value));
+            entry.expires_at = expires_at;
+        }
+        None => {
+            let mut entry = Entry::new(Value::String(value));
+            entry.expires_at = expires_at;
+            entries.insert(key, entry);
+        }
+    }
+}
+
+/// 現在から`duration`後の時刻を返す。
+///
+/// 時刻が表現できない程`duration`が大きい場合は`None`を返す。
+pub(crate) fn deadline_after(duration: Duration) -> Option<Instant> {
+    Instant::now().checked_add(duration)
+}
+
+/// システム時刻`at`を、期限に使う単調増加する時刻に変換する。
+///
+/// `at`が過去の場合は現在の時刻に丸める。時刻が表現できない程`at`が遠い場合は`None`を返す。
+pub(crate) fn instant_at(at: SystemTime) -> Option<Instant> {
+    let now = Instant::now();
+    match at.duration_since(SystemTime::now()) {
+        Ok(remaining) => now.checked_add(remaining),
+        Err(_) => Some(now),
+    }
+}
+
+/// 現在のUNIX時間を秒単位で返す。
+pub(crate) fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+/// 現在のUNIX時間をミリ秒単位で返す。
+pub(crate) fn unix_time_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 削除したエントリをバックグラウンドのスレッドで破棄する。
+///
+/// 大きな値の解放に時間がかかっても、呼び出したコネクションやロックを待つ他のコネクションを止めない。
+pub(crate) fn drop_in_background<T: Send + 'static>(value: T) {
+    tokio::task::spawn_blocking(move || drop(value));
+}
+
+/// 定期的な削除で、1回のロックの間に調べるエントリの数
+const SWEEP_KEYS_PER_LOOP: usize = 20;
+
+/// 定期的な削除で、1周期の間にロックを取り直して調べる回数の上限
+const SWEEP_MAX_LOOPS: usize = 16;
+
+/// ハッシュ値が`cursor`以上のキーから`count`個を調べて、期限切れのエントリを削除する。
+/// 削除した数と次のカーソルを返す。
+///
+/// `SCAN`と同じ索引を辿るため、ロックを保持する時間はエントリの総数ではなく`count`に比例する。
+/// 期限の確認と削除は同じロックの中で行うため、`PERSIST`などで期限が解除されたエントリを削除することはない。
+/// 削除したエントリはロックを解放した後、バックグラウンドで破棄する。
+pub(crate) fn purge_expired(db: &Db, cursor: u64, count: usize) -> (usize, u64) {
+    let now = Instant::now();
+    let mut entries = db.lock().unwrap();
+
+    let (keys, next) = entries.scan(cursor, count);
+    let expired: Vec<_> = keys
+        .into_iter()
+        .filter(|key| entries.get(key).is_some_and(|entry| entry.is_expired(now)))
+        .map(str::to_string)
+        .collect();
+    let removed: Vec<_> = expired
+        .iter()
+        .filter_map(|key| entries.remove(key))
+        .collect();
+    drop(entries);
+
+    let purged = removed.len();
+    if !removed.is_empty() {
+        drop_in_background(removed);
+    }
+
+    (purged, next)
+}
+
+/// `interval`毎に期限切れのエントリを削除する。
+///
+/// Redisの能動的な期限切れの削除と同様に、1周期では一定数のエントリだけを調べ、続きは次の周期で調べる。
+/// 調べたエントリのうち期限切れの割合が4分の1を超える間は、同じ周期の中で続けて調べる。
+/// `stop`に通知されるか、送信側がドロップされると終了する。
+pub(crate) async fn sweep_expired(db: Db, interval: Duration, mut stop: oneshot::Receiver<()>) {
+    let mut ticker = time::interval(interval);
+    let mut cursor = 0;
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                for _ in 0..SWEEP_MAX_LOOPS {
+                    let (purged, next) = purge_expired(&db, cursor, SWEEP_KEYS_PER_LOOP);
+                    cursor = next;
+                    if cursor == 0 || purged * 4 <= SWEEP_KEYS_PER_LOOP {
+                        break;
+                    }
+                }
+            }
+            _ = &mut stop => break,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    /// 破棄を許可されるまで`Drop`が終わらない値
+    pub(crate) struct BlockingDrop {
+        release: mpsc::Receiver<()>,
+        dropped: mpsc::Sender<()>,
+    }
+
+    impl BlockingDrop {
+        /// 値と、破棄を許可する送信側と、破棄が終わったことを受け取る受信側を返す。
+        pub(crate) fn new() -> (BlockingDrop, mpsc::Sender<()>, mpsc::Receiver<()>) {
+            let (release_tx, release_rx) = mpsc::channel();
+            let (dropped_tx, dropped_rx) = mpsc::channel();
+            let value = BlockingDrop {
+                release: release_rx,
+                dropped: dropped_tx,
+            };
+
+            (value, release_tx, dropped_rx)
+        }
+    }
+
+    impl Drop for BlockingDrop {
+        fn drop(&mut self) {
+            let _ = self.release.recv();
+            let _ = self.dropped.send(());
+        }
+    }
+
+    #[tokio::test]
+    async fn drop_in_background_returns_before_drop_finishes() {
+        let (value, release, dropped) = BlockingDrop::new();
+
+        drop_in_background(value);
+        assert!(dropped.try_recv().is_err());
+
+        release.send(()).unwrap();
+        dropped.recv_timeout(Duration::from_secs(5)).unwrap();
+    }
+
+    #[test]
+    fn instant_at_clamps_past_time_to_now() {
+        let before = Instant::now();
+        let at = instant_at(SystemTime::now() - Duration::from_secs(60)).unwrap();
+
+        assert!(before <= at && at <= Instant::now());
+    }
+
+    #[test]
+    fn instant_at_converts_future_time() {
+        let at = instant_at(SystemTime::now() + Duration::from_secs(60)).unwrap();
+        let remaining = at - Instant::now();
+
+        assert!(Duration::from_secs(59) < remaining && remaining <= Duration::from_secs(60));
+    }
+
+    /// `Entries::scan`を最後まで進めて、返されたキーを全て集める。
+    fn scan_all(entries: &Entries, count: usize) -> Vec<String> {
+        let mut cursor = 0;
+        let mut keys = vec![];
+        loop {
+            let (batch, next) = entries.scan(cursor, count);
+            assert!(next == 0 || cursor < next);
+            keys.extend(batch.into_iter().map(String::from));
+            if next == 0 {
+                return keys;
+            }
+            cursor = next;
+        }
+    }
+
+    #[test]
+    fn scan_returns_each_key_once() {
+        let mut entries = Entries::default();
+        for i in 0..100 {
+            entries.insert(format!("key:{}", i), Entry::new(Value::String("v".into())));
+        }
+
+        let (batch, _) = entries.scan(0, 10);
+        assert_eq!(10, batch.len());
+
+        let mut keys = scan_all(&entries, 7);
+        keys.sort();
+        let mut expected: Vec<_> = (0..100).map(|i| format!("key:{}", i)).collect();
+        expected.sort();
+        assert_eq!(expected, keys);
+    }
+
+    #[test]
+    fn scan_index_follows_insert_remove_and_retain() {
+        let mut entries = Entries::default();
+        for key in ["a", "b", "c", "d"] {
+            entries.insert(key.into(), Entry::new(Value::String("v".into())));
+        }
+        // 上書きは索引に重複して登録しない
+        entries.insert("a".into(), Entry::new(Value::String("w".into())));
+        entries.remove("b");
+        entries.retain(|key, _| key != "c");
+
+        let mut keys = scan_all(&entries, 1);
+        keys.sort();
+        assert_eq!(vec!["a", "d"], keys);
+    }
+
+    #[test]
+    fn scan_index_follows_entry() {
+        let mut entries = Entries::default();
+        entries.insert("a".into(), Entry::new(Value::String("v".into())));
+
+        for key in ["a", "b"] {
+            match entries.entry(key.into()) {
+                Slot::Occupied(mut slot) => {
+                    slot.insert(Entry::new(Value::String("w".into())));
+                }
+                Slot::Vacant(slot) => {
+                    slot.insert(Entry::new(Value::String("w".into())));
+                }
+            }
+        }
+
+        let mut keys = scan_all(&entries, 1);
+        keys.sort();
+        assert_eq!(vec!["a", "b"], keys);
+    }
+
+    #[tokio::test]
+    async fn purge_expired_removes_only_expired_entries() {
+        let db = new_db();
+        {
+            let mut entries = db.lock().unwrap();
+            let mut expired = Entry::new(Value::String("old".into()));
+            expired.expires_at = Some(Instant::now() - Duration::from_secs(1));
+            let mut alive = Entry::new(Value::String("new".into()));
+            alive.expires_at = Some(Instant::now() + Duration::from_secs(60));
+            entries.insert("expired".into(), expired);
+            entries.insert("alive".into(), alive);
+            entries.insert(
+                "persistent".into(),
+                Entry::new(Value::String("value".into())),
+            );
+        }
+
+        assert_eq!((1, 0), purge_expired(&db, 0, 10));
+
+        let entries = db.lock().unwrap();
+        assert!(!entries.contains_key("expired"));
+        assert!(entries.contains_key("alive"));
+        assert!(entries.contains_key("persistent"));
+    }
+
+    #[tokio::test]
+    async fn purge_expired_keeps_entries_whose_expiry_was_cleared() {
+        let db = new_db();
+        let mut entry = Entry::new(Value::String("value".into()));
+        entry.expires_at = Some(Instant::now() - Duration::from_secs(1));
+        db.lock().unwrap().insert("key".into(), entry);
+
+        // 期限切れになった後、削除される前に期限を解除する
+        db.lock().unwrap().get_mut("key").unwrap().expires_at = None;
+
+        assert_eq!((0, 0), purge_expired(&db, 0, 10));
+        assert!(db.lock().unwrap().contains_key("key"));
+    }
+
+    #[tokio::test]
+    async fn purge_expired_examines_only_count_keys_per_call() {
+        let db = new_db();
+        for i in 0..10 {
+            let mut entry = Entry::new(Value::String("value".into()));
+            entry.expires_at = Some(Instant::now() - Duration::from_secs(1));
+            db.lock().unwrap().insert(format!("key:{}", i), entry);
+        }
+
+        // 1回の呼び出しでは一部だけを削除し、カーソルを辿ると全て削除する
+        let (purged, mut cursor) = purge_expired(&db, 0, 3);
+        assert!((3..10).contains(&purged), "{}", purged);
+        let mut total = purged;
+        while cursor != 0 {
+            let (purged, next) = purge_expired(&db, cursor, 3);
+            total += purged;
+            cursor = next;
+        }
+
+        assert_eq!(10, total);
+        assert!(db.lock().unwrap().is_empty());
+    }
+}
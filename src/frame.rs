@@ -0,0 +1,359 @@
+//! Redisプロトコルのフレームと、そのバイト列との相互変換
+//!
+//! `mini_redis::Frame`は整数を`u64`で表現し、入れ子になった配列を書き込めないため、
+//! 負の整数を返すコマンドや入れ子の配列を返すコマンドのために独自に定義する。
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::fmt;
+use std::io::Cursor;
+use std::string::FromUtf8Error;
+
+/// Redisプロトコルのフレーム
+#[derive(Clone, Debug, PartialEq)]
+pub enum Frame {
+    Simple(String),
+    Error(String),
+    Integer(i64),
+    Bulk(Bytes),
+    Null,
+    Array(Vec<Frame>),
+}
+
+/// 入れ子にできる配列の深さの上限
+///
+/// 上限がないと、深く入れ子になった配列の解析でスタックを使い果たしてサーバが停止する。
+pub const MAX_DEPTH: usize = 128;
+
+/// バルク文字列の長さの上限(Redisの`proto-max-bulk-len`の既定値)
+pub const MAX_BULK_LEN: usize = 512 * 1024 * 1024;
+
+/// 配列の要素数の上限
+pub const MAX_ARRAY_LEN: usize = 1024 * 1024 * 1024;
+
+/// フレームを解析するときに発生するエラー
+#[derive(Debug)]
+pub enum Error {
+    /// フレームを解析するために十分なデータがない
+    Incomplete,
+
+    /// 不正なフレーム
+    Other(crate::Error),
+}
+
+impl Frame {
+    /// `src`から1つのフレーム全体を解析できるか確認する。
+    ///
+    /// 配列の入れ子が`MAX_DEPTH`より深い場合や、長さが上限を超える場合はエラーを返す。
+    pub fn check(src: &mut Cursor<&[u8]>) -> Result<(), Error> {
+        check_nested(src, 0)
+    }
+
+    /// `src`からフレームを解析する。
+    ///
+    /// `src`は`check`で検証されていなければならない。
+    pub fn parse(src: &mut Cursor<&[u8]>) -> Result<Frame, Error> {
+        parse_nested(src, 0)
+    }
+
+    /// フレームをバイト列に変換して`dst`に追加する。
+    ///
+    /// 配列の要素が配列の場合も再帰的に変換する。
+    pub fn encode(&self, dst: &mut BytesMut) {
+        match self {
+            Frame::Simple(val) => {
+                dst.put_u8(b'+');
+                dst.put_slice(val.as_bytes());
+                dst.put_slice(b"\r\n");
+            }
+            Frame::Error(val) => {
+                dst.put_u8(b'-');
+                dst.put_slice(val.as_bytes());
+                dst.put_slice(b"\r\n");
+            }
+            Frame::Integer(val) => {
+                dst.put_u8(b':');
+                put_decimal(dst, *val);
+            }
+            Frame::Null => {
+                dst.put_slice(b"$-1\r\n");
+            }
+            Frame::Bulk(val) => {
+                dst.put_u8(b'$');
+                put_decimal(dst, val.len() as i64);
+                dst.put_slice(val);
+                dst.put_slice(b"\r\n");
+            }
+            Frame::Array(val) => {
+                dst.put_u8(b'*');
+                put_decimal(dst, val.len() as i64);
+                for entry in val {
+                    entry.encode(dst);
+                }
+            }
+        }
+    }
+}
+
+/// `depth`段の配列の中にある要素として、`src`から1つのフレーム全体を解析できるか確認する。
+fn check_nested(src: &mut Cursor<&[u8]>, depth: usize) -> Result<(), Error> {
+    match get_u8(src)? {
+        b'+' | b'-' => {
+            get_line(src)?;
+            Ok(())
+        }
+        b':' => {
+            get_decimal(src)?;
+            Ok(())
+        }
+        b'$' => {
+            if b'-' == peek_u8(src)? {
+                // "-1\r\n"を読み飛ばす
+                skip(src, 4)
+            } else {
+                let len = get_length(src, MAX_BULK_LEN)?;
+
+                // バルク文字列と"\r\n"を読み飛ばす
+                skip(src, len + 2)
+            }
+        }
+        b'*' => {
+            let len = get_length(src, MAX_ARRAY_LEN)?;
+            let depth = enter_array(depth)?;
+
+            for _ in 0..len {
+                check_nested(src, depth)?;
+            }
+
+            Ok(())
+        }
+        actual => Err(format!("protocol error; invalid frame type byte `{}`", actual).into()),
+    }
+}
+
+/// `depth`段の配列の中にある要素として、`src`からフレームを解析する。
+fn parse_nested(src: &mut Cursor<&[u8]>, depth: usize) -> Result<Frame, Error> {
+    match get_u8(src)? {
+        b'+' => {
+            let line = get_line(src)?.to_vec();
+            Ok(Frame::Simple(String::from_utf8(line)?))
+        }
+        b'-' => {
+            let line = get_line(src)?.to_vec();
+            Ok(Frame::Error(String::from_utf8(line)?))
+        }
+        b':' => Ok(Frame::Integer(get_decimal(src)?)),
+        b'$' => {
+            if b'-' == peek_u8(src)? {
+                let line = get_line(src)?;
+
+                if line != b"-1" {
+                    return Err("protocol error; invalid frame format".into());
+                }
+
+                Ok(Frame::Null)
+            } else {
+                let len = get_length(src, MAX_BULK_LEN)?;
+                let n = len + 2;
+
+                if src.remaining() < n {
+                    return Err(Error::Incomplete);
+                }
+
+                let data = Bytes::copy_from_slice(&src.chunk()[..len]);
+
+                // バルク文字列と"\r\n"を読み飛ばす
+                skip(src, n)?;
+
+                Ok(Frame::Bulk(data))
+            }
+        }
+        b'*' => {
+            let len = get_length(src, MAX_ARRAY_LEN)?;
+            let depth = enter_array(depth)?;
+            let mut out = Vec::with_capacity(len);
+
+            for _ in 0..len {
+                out.push(parse_nested(src, depth)?);
+            }
+
+            Ok(Frame::Array(out))
+        }
+        actual => Err(format!("protocol error; invalid frame type byte `{}`", actual).into()),
+    }
+}
+
+/// `depth`段の配列の中に配列を入れ子にして、その要素の深さを返す。
+fn enter_array(depth: usize) -> Result<usize, Error> {
+    if depth >= MAX_DEPTH {
+        return Err("protocol error; nesting too deep".into());
+    }
+
+    Ok(depth + 1)
+}
+
+fn peek_u8(src: &mut Cursor<&[u8]>) -> Result<u8, Error> {
+    if !src.has_remaining() {
+        return Err(Error::Incomplete);
+    }
+
+    Ok(src.chunk()[0])
+}
+
+fn get_u8(src: &mut Cursor<&[u8]>) -> Result<u8, Error> {
+    if !src.has_remaining() {
+        return Err(Error::Incomplete);
+    }
+
+    Ok(src.get_u8())
+}
+
+fn skip(src: &mut Cursor<&[u8]>, n: usize) -> Result<(), Error> {
+    if src.remaining() < n {
+        return Err(Error::Incomplete);
+    }
+
+    src.advance(n);
+    Ok(())
+}
+
+/// 改行で終わる符号付き10進数を読み込む。
+fn get_decimal(src: &mut Cursor<&[u8]>) -> Result<i64, Error> {
+    let line = get_line(src)?;
+
+    std::str::from_utf8(line)
+        .ok()
+        .and_then(|line| line.parse().ok())
+        .ok_or_else(|| "protocol error; invalid frame format".into())
+}
+
+/// 改行で終わるバルク文字列や配列の長さを読み込む。
+///
+/// 長さが`max`を超える場合はエラーを返す。
+fn get_length(src: &mut Cursor<&[u8]>, max: usize) -> Result<usize, Error> {
+    let len = usize::try_from(get_decimal(src)?)
+        .map_err(|_| Error::from("protocol error; invalid frame format"))?;
+    if len > max {
+        return Err("protocol error; invalid length".into());
+    }
+
+    Ok(len)
+}
+
+/// 改行で終わる1行を読み込む。
+fn get_line<'a>(src: &mut Cursor<&'a [u8]>) -> Result<&'a [u8], Error> {
+    let start = src.position() as usize;
+    let line = &src.get_ref()[start..];
+
+    match line.windows(2).position(|window| window == b"\r\n") {
+        Some(len) => {
+            // 改行の直後に位置を移動
+            src.set_position((start + len + 2) as u64);
+            Ok(&line[..len])
+        }
+        None => Err(Error::Incomplete),
+    }
+}
+
+/// 10進数と改行を書き込む。
+fn put_decimal(dst: &mut BytesMut, val: i64) {
+    dst.put_slice(val.to_string().as_bytes());
+    dst.put_slice(b"\r\n");
+}
+
+impl From<String> for Error {
+    fn from(src: String) -> Error {
+        Error::Other(src.into())
+    }
+}
+
+impl From<&str> for Error {
+    fn from(src: &str) -> Error {
+        src.to_string().into()
+    }
+}
+
+impl From<FromUtf8Error> for Error {
+    fn from(_src: FromUtf8Error) -> Error {
+        "protocol error; invalid frame format".into()
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Incomplete => "stream ended early".fmt(fmt),
+            Error::Other(err) => err.fmt(fmt),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(frame: &Frame) -> Frame {
+        let mut buf = BytesMut::new();
+        frame.encode(&mut buf);
+
+        let mut src = Cursor::new(&buf[..]);
+        Frame::check(&mut src).unwrap();
+        src.set_position(0);
+        Frame::parse(&mut src).unwrap()
+    }
+
+    #[test]
+    fn negative_integer_round_trips() {
+        let frame = Frame::Integer(-2);
+
+        assert_eq!(frame, round_trip(&frame));
+    }
+
+    #[test]
+    fn nested_array_round_trips() {
+        let frame = Frame::Array(vec![
+            Frame::Bulk("0".into()),
+            Frame::Array(vec![Frame::Bulk("a".into()), Frame::Null]),
+        ]);
+
+        assert_eq!(frame, round_trip(&frame));
+    }
+
+    #[test]
+    fn nesting_up_to_max_depth_is_accepted() {
+        let src = "*1\r\n".repeat(MAX_DEPTH) + ":1\r\n";
+        let mut src = Cursor::new(src.as_bytes());
+
+        Frame::check(&mut src).unwrap();
+        src.set_position(0);
+        Frame::parse(&mut src).unwrap();
+    }
+
+    #[test]
+    fn nesting_too_deep_is_rejected() {
+        // 全体が届く前でも、深さが上限を超えた時点でエラーにする
+        let src = "*1\r\n".repeat(MAX_DEPTH + 1);
+        let mut src = Cursor::new(src.as_bytes());
+
+        match Frame::check(&mut src) {
+            Err(Error::Other(err)) => assert!(err.to_string().contains("nesting too deep")),
+            res => panic!("{:?}", res),
+        }
+    }
+
+    #[test]
+    fn too_long_bulk_is_rejected() {
+        let src = format!("${}\r\n", MAX_BULK_LEN + 1);
+        let mut src = Cursor::new(src.as_bytes());
+
+        assert!(matches!(Frame::check(&mut src), Err(Error::Other(_))));
+    }
+
+    #[test]
+    fn partial_frame_is_incomplete() {
+        let mut src = Cursor::new(&b"$5\r\nhel"[..]);
+
+        assert!(matches!(Frame::check(&mut src), Err(Error::Incomplete)));
+    }
+}
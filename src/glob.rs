@@ -0,0 +1,152 @@
+//! Redisの`KEYS`と同じ規則のグロブパターンマッチ
+//!
+//! - `*`: 0文字以上の任意の文字列
+//! - `?`: 任意の1文字
+//! - `[abc]`、`[a-z]`、`[^abc]`: 文字クラスと、その否定
+//! - `\x`: `x`そのもの
+
+/// `string`が`pattern`に一致するか確認する。
+pub(crate) fn matches(pattern: &[u8], string: &[u8]) -> bool {
+    let (mut p, mut s) = (0, 0);
+
+    // 最後に現れた`*`の位置と、その`*`に一致させた文字列の終端
+    let mut star: Option<(usize, usize)> = None;
+
+    while s < string.len() {
+        if p < pattern.len() {
+            match pattern[p] {
+                b'*' => {
+                    star = Some((p, s));
+                    p += 1;
+                    continue;
+                }
+                b'?' => {
+                    p += 1;
+                    s += 1;
+                    continue;
+                }
+                b'[' => {
+                    let (matched, next) = match_class(pattern, p, string[s]);
+                    if matched {
+                        p = next;
+                        s += 1;
+                        continue;
+                    }
+                }
+                b'\\' if p + 1 < pattern.len() => {
+                    if pattern[p + 1] == string[s] {
+                        p += 2;
+                        s += 1;
+                        continue;
+                    }
+                }
+                c => {
+                    if c == string[s] {
+                        p += 1;
+                        s += 1;
+                        continue;
+                    }
+                }
+            }
+        }
+
+        // 一致しなかったため、直前の`*`に1文字多く一致させてやり直す
+        match star {
+            Some((star_p, star_s)) => {
+                p = star_p + 1;
+                s = star_s + 1;
+                star = Some((star_p, star_s + 1));
+            }
+            None => return false,
+        }
+    }
+
+    // 文字列を全て消費した後は、パターンの残りが`*`だけであれば一致する
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// `pattern[start]`の`[`から始まる文字クラスに`c`が一致するか確認する。
+///
+/// 一致するかどうかと、文字クラスの直後の位置を返す。
+/// 閉じ括弧がない場合は、パターンの終端までを文字クラスとして扱う。
+fn match_class(pattern: &[u8], start: usize, c: u8) -> (bool, usize) {
+    let mut i = start + 1;
+
+    let negate = pattern.get(i) == Some(&b'^');
+    if negate {
+        i += 1;
+    }
+
+    let mut matched = false;
+    while i < pattern.len() && pattern[i] != b']' {
+        if pattern[i] == b'\\' && i + 1 < pattern.len() {
+            matched |= pattern[i + 1] == c;
+            i += 2;
+        } else if i + 2 < pattern.len() && pattern[i + 1] == b'-' && pattern[i + 2] != b']' {
+            let (low, high) = if pattern[i] <= pattern[i + 2] {
+                (pattern[i], pattern[i + 2])
+            } else {
+                (pattern[i + 2], pattern[i])
+            };
+            matched |= low <= c && c <= high;
+            i += 3;
+        } else {
+            matched |= pattern[i] == c;
+            i += 1;
+        }
+    }
+
+    // 閉じ括弧を読み飛ばす
+    (matched != negate, (i + 1).min(pattern.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_match(pattern: &str, string: &str) -> bool {
+        matches(pattern.as_bytes(), string.as_bytes())
+    }
+
+    #[test]
+    fn bare_star_matches_everything() {
+        assert!(is_match("*", ""));
+        assert!(is_match("*", "user:1"));
+        assert!(is_match("**", "anything"));
+    }
+
+    #[test]
+    fn empty_pattern_matches_only_empty_string() {
+        assert!(is_match("", ""));
+        assert!(!is_match("", "a"));
+    }
+
+    #[test]
+    fn star_and_question_mark() {
+        assert!(is_match("user:*", "user:42"));
+        assert!(is_match("*:name", "user:42:name"));
+        assert!(is_match("h?llo", "hello"));
+        assert!(!is_match("h?llo", "hllo"));
+        assert!(is_match("a*b*c", "axxbyyc"));
+        assert!(!is_match("a*b*c", "axxbyy"));
+    }
+
+    #[test]
+    fn character_classes() {
+        assert!(is_match("h[ae]llo", "hallo"));
+        assert!(!is_match("h[ae]llo", "hillo"));
+        assert!(is_match("h[^e]llo", "hallo"));
+        assert!(!is_match("h[^e]llo", "hello"));
+        assert!(is_match("key[0-9]", "key7"));
+        assert!(!is_match("key[0-9]", "keyx"));
+    }
+
+    #[test]
+    fn escaped_brackets_match_literally() {
+        assert!(is_match(r"\[abc\]", "[abc]"));
+        assert!(!is_match(r"\[abc\]", "a"));
+        assert!(is_match(r"[\]]", "]"));
+        assert!(is_match(r"a\*", "a*"));
+        assert!(!is_match(r"a\*", "ab"));
+    }
+}
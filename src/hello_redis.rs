@@ -0,0 +1,32 @@
+//! `hello`キーに`world`を設定して取得するチュートリアルのフロー
+
+use bytes::Bytes;
+use mini_redis::{client, Result};
+
+/// 接続先アドレスを指定する環境変数
+pub const ADDR_ENV: &str = "MY_REDIS_ADDR";
+
+/// 接続先アドレスを指定しなかった場合のデフォルト
+pub const DEFAULT_ADDR: &str = "localhost:6379";
+
+/// 接続先アドレスを決定する。
+///
+/// コマンドライン引数、環境変数`MY_REDIS_ADDR`、デフォルトの順に優先する。
+pub fn server_addr(arg: Option<String>) -> String {
+    arg.or_else(|| std::env::var(ADDR_ENV).ok())
+        .unwrap_or_else(|| DEFAULT_ADDR.to_string())
+}
+
+/// `addr`のサーバに"hello"="world"をセットして、キー"hello"の値を返す。
+pub async fn run(addr: &str) -> Result<Option<Bytes>> {
+    // mini-redisアドレスへのコネクションを開く
+    let mut client = client::connect(addr).await?;
+
+    // "hello"というキーに"world"という値をセット
+    client.set("hello", "world".into()).await?;
+
+    // キー"hello"の値を取得
+    let result = client.get("hello").await?;
+
+    Ok(result)
+}
@@ -0,0 +1,27 @@
+//! チュートリアルで作成するmini-redisクライアント及びサーバの共通コード
+
+mod cmd;
+
+pub mod connection;
+pub use connection::Connection;
+
+pub mod db;
+pub use db::{Db, Entries, Entry, Value};
+
+pub mod frame;
+pub use frame::Frame;
+
+mod glob;
+
+pub mod hello_redis;
+
+mod parse;
+
+pub mod server;
+pub use server::Config;
+
+/// このクレートの関数が返すエラー
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// このクレートの関数が返す`Result`
+pub type Result<T> = std::result::Result<T, Error>;
@@ -1,15 +1,12 @@
-use mini_redis::{client, Result};
+use mini_redis::Result;
+use my_redis::hello_redis;
 
 #[tokio::main]
 pub async fn main() -> Result<()> {
-    // mini-redisアドレスへのコネクションを開く
-    let mut client = client::connect("localhost:6379").await?;
+    // 接続先は引数または環境変数MY_REDIS_ADDRで指定できる
+    let addr = hello_redis::server_addr(std::env::args().nth(1));
 
-    // "hello"というキーに"world"という値をセット
-    client.set("hello", "world".into()).await?;
-
-    // キー"hello"の値を取得
-    let result = client.get("hello").await?;
+    let result = hello_redis::run(&addr).await?;
 
     println!("サーバから値を取得しました; result={:?}", result);
 
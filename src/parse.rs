@@ -0,0 +1,119 @@
+//! コマンドを表現する配列フレームから引数を順番に取り出すユーティリティ
+
+use crate::Frame;
+use bytes::Bytes;
+use std::{fmt, str, vec};
+
+/// 配列フレームの要素を先頭から順に取り出すカーソル
+#[derive(Debug)]
+pub(crate) struct Parse {
+    parts: vec::IntoIter<Frame>,
+}
+
+/// 引数を取り出すときに発生するエラー
+#[derive(Debug)]
+pub(crate) enum ParseError {
+    /// 引数が足りない
+    EndOfStream,
+
+    /// その他の不正な引数
+    Other(String),
+}
+
+impl Parse {
+    /// 配列フレームから`Parse`を作成する。
+    ///
+    /// 配列フレーム以外の場合はエラーを返す。
+    pub(crate) fn new(frame: Frame) -> Result<Parse, ParseError> {
+        let array = match frame {
+            Frame::Array(array) => array,
+            frame => {
+                return Err(ParseError::Other(format!(
+                    "protocol error; expected array, got {:?}",
+                    frame
+                )))
+            }
+        };
+
+        Ok(Parse {
+            parts: array.into_iter(),
+        })
+    }
+
+    /// 次の要素を返す。
+    fn next(&mut self) -> Result<Frame, ParseError> {
+        self.parts.next().ok_or(ParseError::EndOfStream)
+    }
+
+    /// 次の要素を文字列として返す。
+    pub(crate) fn next_string(&mut self) -> Result<String, ParseError> {
+        match self.next()? {
+            Frame::Simple(s) => Ok(s),
+            Frame::Bulk(data) => str::from_utf8(&data[..])
+                .map(|s| s.to_string())
+                .map_err(|_| ParseError::Other("protocol error; invalid string".into())),
+            frame => Err(ParseError::Other(format!(
+                "protocol error; expected simple frame or bulk frame, got {:?}",
+                frame
+            ))),
+        }
+    }
+
+    /// 残りの要素を全て文字列として返す。要素は1つ以上必要である。
+    pub(crate) fn next_strings(&mut self) -> Result<Vec<String>, ParseError> {
+        let mut strings = vec![self.next_string()?];
+        while self.has_remaining() {
+            strings.push(self.next_string()?);
+        }
+
+        Ok(strings)
+    }
+
+    /// 次の要素をバイト列として返す。
+    pub(crate) fn next_bytes(&mut self) -> Result<Bytes, ParseError> {
+        match self.next()? {
+            Frame::Simple(s) => Ok(Bytes::from(s.into_bytes())),
+            Frame::Bulk(data) => Ok(data),
+            frame => Err(ParseError::Other(format!(
+                "protocol error; expected simple frame or bulk frame, got {:?}",
+                frame
+            ))),
+        }
+    }
+
+    /// 次の要素を符号付き整数として返す。
+    pub(crate) fn next_int(&mut self) -> Result<i64, ParseError> {
+        const MSG: &str = "value is not an integer or out of range";
+
+        self.next_string()?
+            .parse()
+            .map_err(|_| ParseError::Other(MSG.into()))
+    }
+
+    /// 全ての要素を取り出したことを確認する。
+    pub(crate) fn finish(&mut self) -> Result<(), ParseError> {
+        if self.parts.next().is_none() {
+            Ok(())
+        } else {
+            Err(ParseError::Other(
+                "protocol error; expected end of frame, but there was more".into(),
+            ))
+        }
+    }
+
+    /// 取り出していない要素が残っているか確認する。
+    pub(crate) fn has_remaining(&self) -> bool {
+        self.parts.len() > 0
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::EndOfStream => "protocol error; unexpected end of stream".fmt(f),
+            ParseError::Other(msg) => msg.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -0,0 +1,189 @@
+//! mini-redisサーバの実装
+
+use crate::cmd;
+use crate::db::{self, Db};
+use crate::parse::{Parse, ParseError};
+use crate::{Connection, Frame};
+use std::future::Future;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use tokio::time::Duration;
+
+/// サーバの設定
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// バックグラウンドで期限切れのキーを削除する間隔
+    pub sweep_interval: Duration,
+
+    /// `SETRANGE`などで作成できる文字列の値の最大バイト数
+    pub max_value_len: usize,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            sweep_interval: Duration::from_millis(100),
+            max_value_len: 512 * 1024 * 1024,
+        }
+    }
+}
+
+/// `listener`で受け付けたコネクションを、`shutdown`が完了するまで処理する。
+///
+/// 期限切れのキーを削除するバックグラウンドタスクは、コネクションの受付を終了したときに停止する。
+pub async fn run(listener: TcpListener, config: Config, shutdown: impl Future) {
+    // 全てのコネクションで共有するデータベースを作成
+    let db = db::new_db();
+
+    // 期限切れのキーを削除するタスクを起動
+    let (stop_tx, stop_rx) = oneshot::channel();
+    let sweeper = tokio::spawn(db::sweep_expired(
+        db.clone(),
+        config.sweep_interval,
+        stop_rx,
+    ));
+
+    tokio::select! {
+        _ = accept(&listener, &db, &config) => {}
+        _ = shutdown => {}
+    }
+
+    // 受付を終了したため、バックグラウンドタスクを停止
+    let _ = stop_tx.send(());
+    let _ = sweeper.await;
+}
+
+/// コネクションを受け付けて、コネクション毎にタスクを起動する。
+///
+/// コネクションの受付に失敗した場合に戻る。
+async fn accept(listener: &TcpListener, db: &Db, config: &Config) {
+    loop {
+        let socket = match listener.accept().await {
+            Ok((socket, _)) => socket,
+            Err(err) => {
+                eprintln!("コネクションの受付に失敗しました。{}", err);
+                return;
+            }
+        };
+
+        // ハッシュマップのハンドルと設定を複製
+        let db = db.clone();
+        let config = config.clone();
+
+        // 受け付けたソケット毎に新しいタスクを起動
+        tokio::spawn(async move {
+            process(socket, db, config).await;
+        });
+    }
+}
+
+async fn process(socket: TcpStream, db: Db, config: Config) {
+    // `Connection`はソケットから受信したバイト列をフレームに変換する
+    let mut connection = Connection::new(socket);
+
+    loop {
+        let frame = match connection.read_frame().await {
+            Ok(Some(frame)) => frame,
+            // クライアントがコネクションを閉じた
+            Ok(None) => return,
+            // RESPとして解釈できないバイト列を受信した場合は、エラーを返してコネクションを閉じる
+            Err(err) => {
+                let response = Frame::Error(format!("ERR Protocol error: {}", err));
+                let _ = connection.write_frame(&response).await;
+                return;
+            }
+        };
+        let response = dispatch(&db, &config, frame);
+
+        // クライアントに応答を書き込み、書き込めない場合はコネクションを閉じる
+        if connection.write_frame(&response).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// 配列フレームの先頭要素をコマンド名として、対応するコマンドを実行する。
+///
+/// フレームがコマンドとして解釈できない場合や、コマンドが実装されていない場合はエラーフレームを返す。
+fn dispatch(db: &Db, config: &Config, frame: Frame) -> Frame {
+    let mut parse = match Parse::new(frame) {
+        Ok(parse) => parse,
+        Err(err) => return Frame::Error(format!("ERR {}", err)),
+    };
+    let command = match parse.next_string() {
+        Ok(command) => command,
+        Err(err) => return Frame::Error(format!("ERR {}", err)),
+    };
+    let name = command.to_lowercase();
+
+    let handler: cmd::Handler = match &name[..] {
+        "ping" => cmd::ping,
+        "echo" => cmd::echo,
+        "get" => cmd::get,
+        "mget" => cmd::mget,
+        "mset" => cmd::mset,
+        "msetnx" => cmd::msetnx,
+        "getex" => cmd::getex,
+        "getdel" => cmd::getdel,
+        "getrange" => cmd::getrange,
+        "setrange" => cmd::setrange,
+        "set" => cmd::set,
+        "setnx" => cmd::setnx,
+        "getset" => cmd::getset,
+        "append" => cmd::append,
+        "strlen" => cmd::strlen,
+        "incr" => cmd::incr,
+        "decr" => cmd::decr,
+        "incrby" => cmd::incrby,
+        "decrby" => cmd::decrby,
+        "incrbyfloat" => cmd::incrbyfloat,
+        "hset" => cmd::hset,
+        "hget" => cmd::hget,
+        "hdel" => cmd::hdel,
+        "hgetall" => cmd::hgetall,
+        "del" => cmd::del,
+        "unlink" => cmd::unlink,
+        "copy" => cmd::copy,
+        "exists" => cmd::exists,
+        "touch" => cmd::touch,
+        "keys" => cmd::keys,
+        "type" => cmd::type_,
+        "scan" => cmd::scan,
+        "randomkey" => cmd::randomkey,
+        "rename" => cmd::rename,
+        "renamenx" => cmd::renamenx,
+        "expire" => cmd::expire,
+        "expireat" => cmd::expireat,
+        "pexpireat" => cmd::pexpireat,
+        "persist" => cmd::persist,
+        "ttl" => cmd::ttl,
+        "pttl" => cmd::pttl,
+        "dbsize" => cmd::dbsize,
+        "flushdb" => cmd::flushdb,
+        "flushall" => cmd::flushall,
+        "object" => cmd::object,
+        "debug" => cmd::debug,
+        _ => {
+            return Frame::Error(format!(
+                "ERR unknown command '{}'",
+                cmd::printable(&command)
+            ))
+        }
+    };
+
+    let cx = cmd::Context { db, config };
+    reply(&name, handler(&cx, &mut parse))
+}
+
+/// コマンドの実行結果を応答フレームに変換する。
+///
+/// 引数の解析に失敗した場合は、エラーフレームを返す。
+fn reply(name: &str, result: Result<Frame, ParseError>) -> Frame {
+    result.unwrap_or_else(|err| match err {
+        ParseError::EndOfStream => Frame::Error(format!(
+            "ERR wrong number of arguments for '{}' command",
+            name
+        )),
+        ParseError::Other(msg) => Frame::Error(format!("ERR {}", msg)),
+    })
+}
@@ -0,0 +1,44 @@
+//! 統合テストで共有するヘルパ
+
+#![allow(dead_code)]
+
+use bytes::Bytes;
+use my_redis::{Config, Connection, Frame};
+use std::net::SocketAddr;
+use tokio::net::{TcpListener, TcpStream};
+
+/// サーバをランダムなポートで起動して、そのアドレスを返す。
+pub async fn start_server() -> SocketAddr {
+    start_server_with(Config::default()).await
+}
+
+/// `config`でサーバをランダムなポートで起動して、そのアドレスを返す。
+pub async fn start_server_with(config: Config) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(my_redis::server::run(
+        listener,
+        config,
+        std::future::pending::<()>(),
+    ));
+
+    addr
+}
+
+/// サーバにフレームを直接送受信するコネクションを開く。
+pub async fn connect(addr: SocketAddr) -> Connection {
+    Connection::new(TcpStream::connect(addr).await.unwrap())
+}
+
+/// `args`で構成したコマンドを送信して、応答フレームを返す。
+pub async fn send(connection: &mut Connection, args: &[&str]) -> Frame {
+    let frame = Frame::Array(
+        args.iter()
+            .map(|arg| Frame::Bulk(Bytes::copy_from_slice(arg.as_bytes())))
+            .collect(),
+    );
+    connection.write_frame(&frame).await.unwrap();
+
+    connection.read_frame().await.unwrap().unwrap()
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use bytes::Bytes;
+use common::{connect, send, start_server};
+use my_redis::Frame;
+
+#[tokio::test]
+async fn ping_replies_pong() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["PING"]).await;
+    assert_eq!(Frame::Simple("PONG".into()), response);
+
+    let response = send(&mut connection, &["ping", "hello"]).await;
+    assert_eq!(Frame::Bulk("hello".into()), response);
+
+    let response = send(&mut connection, &["PING", "a", "b"]).await;
+    assert!(matches!(response, Frame::Error(_)), "{:?}", response);
+}
+
+#[tokio::test]
+async fn ping_and_echo_return_binary_payload() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    // 区切り文字やUTF-8として不正なバイトを含む値も、そのまま返す
+    let payload = Bytes::from_static(b"\x00\r\n\xff$-1\r\n");
+    for command in ["PING", "ECHO"] {
+        let frame = Frame::Array(vec![
+            Frame::Bulk(command.into()),
+            Frame::Bulk(payload.clone()),
+        ]);
+        connection.write_frame(&frame).await.unwrap();
+
+        let response = connection.read_frame().await.unwrap().unwrap();
+        assert_eq!(Frame::Bulk(payload.clone()), response);
+    }
+}
+
+#[tokio::test]
+async fn echo_requires_message() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["ECHO", "hello"]).await;
+    assert_eq!(Frame::Bulk("hello".into()), response);
+
+    let response = send(&mut connection, &["ECHO"]).await;
+    assert_eq!(
+        Frame::Error("ERR wrong number of arguments for 'echo' command".into()),
+        response
+    );
+}
@@ -0,0 +1,20 @@
+mod common;
+
+use common::start_server;
+use my_redis::hello_redis;
+
+#[tokio::test]
+async fn hello_redis_sets_and_gets_hello() {
+    let addr = start_server().await;
+
+    let result = hello_redis::run(&addr.to_string()).await.unwrap();
+
+    assert_eq!(Some("world".into()), result);
+}
+
+#[test]
+fn server_addr_prefers_argument() {
+    let addr = hello_redis::server_addr(Some("127.0.0.1:7000".to_string()));
+
+    assert_eq!("127.0.0.1:7000", addr);
+}
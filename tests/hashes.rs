@@ -0,0 +1,150 @@
+mod common;
+
+use common::{connect, send, start_server};
+use my_redis::Frame;
+
+/// `HGETALL`の応答を、フィールドの順に並べたフィールドと値の組に変換する。
+fn sorted_pairs(response: Frame) -> Vec<(String, String)> {
+    let items = match response {
+        Frame::Array(items) => items,
+        frame => panic!("{:?}", frame),
+    };
+    let mut pairs: Vec<_> = items
+        .chunks(2)
+        .map(|pair| match pair {
+            [Frame::Bulk(field), Frame::Bulk(value)] => (
+                String::from_utf8(field.to_vec()).unwrap(),
+                String::from_utf8(value.to_vec()).unwrap(),
+            ),
+            pair => panic!("{:?}", pair),
+        })
+        .collect();
+    pairs.sort();
+
+    pairs
+}
+
+#[tokio::test]
+async fn hset_and_hget() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(
+        &mut connection,
+        &["HSET", "user", "name", "alice", "age", "30"],
+    )
+    .await;
+    assert_eq!(Frame::Integer(2), response);
+
+    let response = send(&mut connection, &["HGET", "user", "name"]).await;
+    assert_eq!(Frame::Bulk("alice".into()), response);
+
+    let response = send(&mut connection, &["HGET", "user", "missing"]).await;
+    assert_eq!(Frame::Null, response);
+    let response = send(&mut connection, &["HGET", "missing", "name"]).await;
+    assert_eq!(Frame::Null, response);
+
+    let response = send(&mut connection, &["TYPE", "user"]).await;
+    assert_eq!(Frame::Simple("hash".into()), response);
+    let response = send(&mut connection, &["OBJECT", "ENCODING", "user"]).await;
+    assert_eq!(Frame::Bulk("hashtable".into()), response);
+}
+
+#[tokio::test]
+async fn hset_overwrites_existing_field() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["HSET", "user", "name", "alice"]).await;
+
+    // 既存のフィールドは上書きするが、新たに追加した数には含めない
+    let response = send(
+        &mut connection,
+        &["HSET", "user", "name", "bob", "age", "30"],
+    )
+    .await;
+    assert_eq!(Frame::Integer(1), response);
+
+    let response = send(&mut connection, &["HGET", "user", "name"]).await;
+    assert_eq!(Frame::Bulk("bob".into()), response);
+}
+
+#[tokio::test]
+async fn hdel_removes_fields() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(
+        &mut connection,
+        &["HSET", "user", "name", "alice", "age", "30"],
+    )
+    .await;
+
+    let response = send(&mut connection, &["HDEL", "user", "name", "missing"]).await;
+    assert_eq!(Frame::Integer(1), response);
+    let response = send(&mut connection, &["HGET", "user", "name"]).await;
+    assert_eq!(Frame::Null, response);
+
+    // 最後のフィールドを削除すると、キーも削除する
+    let response = send(&mut connection, &["HDEL", "user", "age"]).await;
+    assert_eq!(Frame::Integer(1), response);
+    let response = send(&mut connection, &["EXISTS", "user"]).await;
+    assert_eq!(Frame::Integer(0), response);
+
+    let response = send(&mut connection, &["HDEL", "user", "age"]).await;
+    assert_eq!(Frame::Integer(0), response);
+}
+
+#[tokio::test]
+async fn hgetall_returns_every_pair() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(
+        &mut connection,
+        &["HSET", "user", "name", "alice", "age", "30"],
+    )
+    .await;
+    send(&mut connection, &["HSET", "user", "city", "tokyo"]).await;
+
+    let response = send(&mut connection, &["HGETALL", "user"]).await;
+    assert_eq!(
+        vec![
+            ("age".to_string(), "30".to_string()),
+            ("city".to_string(), "tokyo".to_string()),
+            ("name".to_string(), "alice".to_string()),
+        ],
+        sorted_pairs(response)
+    );
+
+    let response = send(&mut connection, &["HGETALL", "missing"]).await;
+    assert_eq!(Frame::Array(vec![]), response);
+}
+
+#[tokio::test]
+async fn hash_commands_reject_string_value() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+
+    let wrong_type =
+        Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".into());
+    for command in [
+        &["HSET", "foo", "field", "value"][..],
+        &["HGET", "foo", "field"],
+        &["HDEL", "foo", "field"],
+        &["HGETALL", "foo"],
+    ] {
+        let response = send(&mut connection, command).await;
+        assert_eq!(wrong_type, response, "{:?}", command);
+    }
+
+    // 文字列のコマンドもハッシュを拒否する
+    send(&mut connection, &["HSET", "user", "name", "alice"]).await;
+    let response = send(&mut connection, &["GET", "user"]).await;
+    assert_eq!(wrong_type, response);
+
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Bulk("bar".into()), response);
+}
@@ -0,0 +1,651 @@
+mod common;
+
+use common::{connect, send, start_server, start_server_with};
+use my_redis::{Config, Frame};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+#[tokio::test]
+async fn del_returns_number_of_removed_keys() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "1"]).await;
+    send(&mut connection, &["SET", "bar", "2"]).await;
+
+    let response = send(&mut connection, &["DEL", "foo", "bar", "missing"]).await;
+    assert_eq!(Frame::Integer(2), response);
+
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Null, response);
+}
+
+#[tokio::test]
+async fn del_without_keys_is_an_error() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["DEL"]).await;
+    assert!(matches!(response, Frame::Error(_)), "{:?}", response);
+}
+
+#[tokio::test]
+async fn exists_counts_present_keys() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "1"]).await;
+    send(&mut connection, &["SET", "bar", "2"]).await;
+
+    let response = send(&mut connection, &["EXISTS", "foo", "missing", "bar"]).await;
+    assert_eq!(Frame::Integer(2), response);
+
+    let response = send(&mut connection, &["EXISTS", "missing"]).await;
+    assert_eq!(Frame::Integer(0), response);
+}
+
+#[tokio::test]
+async fn exists_counts_duplicate_keys_each_time() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "1"]).await;
+
+    let response = send(&mut connection, &["EXISTS", "foo", "foo", "foo"]).await;
+    assert_eq!(Frame::Integer(3), response);
+}
+
+#[tokio::test]
+async fn expire_removes_key_after_deadline() {
+    // バックグラウンドタスクが動かなくても、期限切れのキーは取得できない
+    let addr = start_server_with(Config {
+        sweep_interval: Duration::from_secs(3600),
+        ..Config::default()
+    })
+    .await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+
+    let response = send(&mut connection, &["EXPIRE", "foo", "1"]).await;
+    assert_eq!(Frame::Integer(1), response);
+
+    let response = send(&mut connection, &["EXPIRE", "missing", "1"]).await;
+    assert_eq!(Frame::Integer(0), response);
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Null, response);
+
+    let response = send(&mut connection, &["EXISTS", "foo"]).await;
+    assert_eq!(Frame::Integer(0), response);
+}
+
+#[tokio::test]
+async fn expire_with_non_positive_seconds_deletes_key() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+
+    let response = send(&mut connection, &["EXPIRE", "foo", "0"]).await;
+    assert_eq!(Frame::Integer(1), response);
+
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Null, response);
+}
+
+#[tokio::test]
+async fn expire_rejects_non_integer_seconds() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+
+    let response = send(&mut connection, &["EXPIRE", "foo", "soon"]).await;
+    assert!(matches!(response, Frame::Error(_)), "{:?}", response);
+
+    let response = send(&mut connection, &["EXPIRE", "foo", "9223372036854775807"]).await;
+    assert!(matches!(response, Frame::Error(_)), "{:?}", response);
+}
+
+#[tokio::test]
+async fn run_returns_after_shutdown() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+    let server = tokio::spawn(my_redis::server::run(
+        listener,
+        Config::default(),
+        shutdown_rx,
+    ));
+
+    shutdown_tx.send(()).unwrap();
+
+    // バックグラウンドタスクが停止しなければ`run`は戻らない
+    tokio::time::timeout(Duration::from_secs(1), server)
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn ttl_of_missing_and_persistent_keys() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["TTL", "missing"]).await;
+    assert_eq!(Frame::Integer(-2), response);
+
+    let response = send(&mut connection, &["PTTL", "missing"]).await;
+    assert_eq!(Frame::Integer(-2), response);
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+
+    let response = send(&mut connection, &["TTL", "foo"]).await;
+    assert_eq!(Frame::Integer(-1), response);
+
+    let response = send(&mut connection, &["PTTL", "foo"]).await;
+    assert_eq!(Frame::Integer(-1), response);
+}
+
+#[tokio::test]
+async fn ttl_decreases_over_time() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+    send(&mut connection, &["EXPIRE", "foo", "10"]).await;
+
+    let response = send(&mut connection, &["TTL", "foo"]).await;
+    assert_eq!(Frame::Integer(10), response);
+
+    let before = match send(&mut connection, &["PTTL", "foo"]).await {
+        Frame::Integer(ms) => ms,
+        frame => panic!("{:?}", frame),
+    };
+    assert!(9_000 < before && before <= 10_000, "{}", before);
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let after = match send(&mut connection, &["PTTL", "foo"]).await {
+        Frame::Integer(ms) => ms,
+        frame => panic!("{:?}", frame),
+    };
+    assert!(after <= before - 200, "{} -> {}", before, after);
+}
+
+#[tokio::test]
+async fn persist_clears_expiry() {
+    let addr = start_server_with(Config {
+        sweep_interval: Duration::from_millis(10),
+        ..Config::default()
+    })
+    .await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+    send(&mut connection, &["EXPIRE", "foo", "1"]).await;
+
+    let response = send(&mut connection, &["PERSIST", "foo"]).await;
+    assert_eq!(Frame::Integer(1), response);
+
+    // 期限が設定されていないキーや存在しないキーは0
+    let response = send(&mut connection, &["PERSIST", "foo"]).await;
+    assert_eq!(Frame::Integer(0), response);
+    let response = send(&mut connection, &["PERSIST", "missing"]).await;
+    assert_eq!(Frame::Integer(0), response);
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Bulk("bar".into()), response);
+
+    let response = send(&mut connection, &["TTL", "foo"]).await;
+    assert_eq!(Frame::Integer(-1), response);
+}
+
+#[tokio::test]
+async fn keys_matches_prefix_pattern() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    for i in 0..10 {
+        send(&mut connection, &["SET", &format!("user:{}", i), "1"]).await;
+        send(&mut connection, &["SET", &format!("session:{}", i), "1"]).await;
+    }
+    // 期限切れのキーは削除される前でも返さない
+    send(&mut connection, &["SET", "user:expired", "1", "PX", "10"]).await;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    let mut keys = match send(&mut connection, &["KEYS", "user:*"]).await {
+        Frame::Array(keys) => keys,
+        frame => panic!("{:?}", frame),
+    };
+    keys.sort_by_key(|key| format!("{:?}", key));
+
+    let expected: Vec<_> = (0..10)
+        .map(|i| Frame::Bulk(format!("user:{}", i).into()))
+        .collect();
+    assert_eq!(expected, keys);
+
+    let response = send(&mut connection, &["KEYS", "nothing:*"]).await;
+    assert_eq!(Frame::Array(vec![]), response);
+}
+
+/// `SCAN`のカーソルを最後まで進めて、返されたキーを全て集める。
+async fn scan_all(connection: &mut my_redis::Connection, options: &[&str]) -> Vec<String> {
+    let mut cursor = "0".to_string();
+    let mut keys = vec![];
+    loop {
+        let mut command = vec!["SCAN", &cursor];
+        command.extend_from_slice(options);
+        let (next, batch) = match send(connection, &command).await {
+            Frame::Array(mut response) => match (response.remove(0), response.remove(0)) {
+                (Frame::Bulk(next), Frame::Array(batch)) => (next, batch),
+                frame => panic!("{:?}", frame),
+            },
+            frame => panic!("{:?}", frame),
+        };
+        for key in batch {
+            match key {
+                Frame::Bulk(key) => keys.push(String::from_utf8(key.to_vec()).unwrap()),
+                frame => panic!("{:?}", frame),
+            }
+        }
+        cursor = String::from_utf8(next.to_vec()).unwrap();
+        if cursor == "0" {
+            return keys;
+        }
+    }
+}
+
+#[tokio::test]
+async fn scan_walks_every_key() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    for i in 0..300 {
+        send(&mut connection, &["SET", &format!("key:{}", i), "1"]).await;
+    }
+
+    let mut keys = scan_all(&mut connection, &["COUNT", "10"]).await;
+    keys.sort();
+    keys.dedup();
+    let mut expected: Vec<_> = (0..300).map(|i| format!("key:{}", i)).collect();
+    expected.sort();
+    assert_eq!(expected, keys);
+}
+
+#[tokio::test]
+async fn scan_returns_keys_present_for_whole_scan_despite_writes() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+    let mut writer = connect(addr).await;
+
+    for i in 0..300 {
+        send(&mut connection, &["SET", &format!("key:{}", i), "1"]).await;
+    }
+
+    // 走査の途中でキーを追加・削除しても、ずっと存在するキーは漏れない
+    let mut cursor = "0".to_string();
+    let mut keys = vec![];
+    let mut round = 0;
+    loop {
+        let (next, batch) = match send(&mut connection, &["SCAN", &cursor, "COUNT", "10"]).await {
+            Frame::Array(mut response) => match (response.remove(0), response.remove(0)) {
+                (Frame::Bulk(next), Frame::Array(batch)) => (next, batch),
+                frame => panic!("{:?}", frame),
+            },
+            frame => panic!("{:?}", frame),
+        };
+        keys.extend(batch);
+        send(&mut writer, &["SET", &format!("new:{}", round), "1"]).await;
+        send(&mut writer, &["DEL", &format!("key:{}", 200 + round)]).await;
+        round += 1;
+
+        cursor = String::from_utf8(next.to_vec()).unwrap();
+        if cursor == "0" {
+            break;
+        }
+    }
+
+    for i in 0..200 {
+        let key = Frame::Bulk(format!("key:{}", i).into());
+        assert!(keys.contains(&key), "key:{} が返されていない", i);
+    }
+}
+
+#[tokio::test]
+async fn scan_filters_with_match() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    for i in 0..100 {
+        send(&mut connection, &["SET", &format!("user:{}", i), "1"]).await;
+        send(&mut connection, &["SET", &format!("session:{}", i), "1"]).await;
+    }
+
+    let mut keys = scan_all(&mut connection, &["MATCH", "user:*", "COUNT", "10"]).await;
+    keys.sort();
+    let mut expected: Vec<_> = (0..100).map(|i| format!("user:{}", i)).collect();
+    expected.sort();
+    assert_eq!(expected, keys);
+}
+
+#[tokio::test]
+async fn scan_rejects_invalid_arguments() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["SCAN", "abc"]).await;
+    assert_eq!(Frame::Error("ERR invalid cursor".into()), response);
+
+    let response = send(&mut connection, &["SCAN", "0", "COUNT", "0"]).await;
+    assert_eq!(Frame::Error("ERR syntax error".into()), response);
+
+    let response = send(&mut connection, &["SCAN", "0", "FOO"]).await;
+    assert_eq!(Frame::Error("ERR syntax error".into()), response);
+}
+
+#[tokio::test]
+async fn rename_moves_value_and_ttl() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar", "EX", "100"]).await;
+    send(&mut connection, &["SET", "baz", "old"]).await;
+
+    let response = send(&mut connection, &["RENAME", "foo", "baz"]).await;
+    assert_eq!(Frame::Simple("OK".into()), response);
+
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Null, response);
+    let response = send(&mut connection, &["GET", "baz"]).await;
+    assert_eq!(Frame::Bulk("bar".into()), response);
+    match send(&mut connection, &["TTL", "baz"]).await {
+        Frame::Integer(ttl) => assert!(0 < ttl && ttl <= 100),
+        frame => panic!("{:?}", frame),
+    }
+}
+
+#[tokio::test]
+async fn rename_onto_itself_is_noop() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar", "EX", "100"]).await;
+
+    let response = send(&mut connection, &["RENAME", "foo", "foo"]).await;
+    assert_eq!(Frame::Simple("OK".into()), response);
+
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Bulk("bar".into()), response);
+    match send(&mut connection, &["TTL", "foo"]).await {
+        Frame::Integer(ttl) => assert!(0 < ttl && ttl <= 100),
+        frame => panic!("{:?}", frame),
+    }
+}
+
+#[tokio::test]
+async fn rename_missing_key() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["RENAME", "foo", "bar"]).await;
+    assert_eq!(Frame::Error("ERR no such key".into()), response);
+
+    let response = send(&mut connection, &["RENAMENX", "foo", "bar"]).await;
+    assert_eq!(Frame::Error("ERR no such key".into()), response);
+}
+
+#[tokio::test]
+async fn renamenx_does_not_overwrite() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "1"]).await;
+    send(&mut connection, &["SET", "bar", "2"]).await;
+
+    let response = send(&mut connection, &["RENAMENX", "foo", "bar"]).await;
+    assert_eq!(Frame::Integer(0), response);
+    let response = send(&mut connection, &["GET", "bar"]).await;
+    assert_eq!(Frame::Bulk("2".into()), response);
+
+    let response = send(&mut connection, &["RENAMENX", "foo", "baz"]).await;
+    assert_eq!(Frame::Integer(1), response);
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Null, response);
+    let response = send(&mut connection, &["GET", "baz"]).await;
+    assert_eq!(Frame::Bulk("1".into()), response);
+}
+
+#[tokio::test]
+async fn type_reports_value_type() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+
+    let response = send(&mut connection, &["TYPE", "foo"]).await;
+    assert_eq!(Frame::Simple("string".into()), response);
+
+    let response = send(&mut connection, &["TYPE", "missing"]).await;
+    assert_eq!(Frame::Simple("none".into()), response);
+}
+
+#[tokio::test]
+async fn randomkey_returns_null_when_empty() {
+    // 期限切れのキーが削除されないように、定期的な削除を実質的に止める
+    let addr = start_server_with(Config {
+        sweep_interval: Duration::from_secs(3600),
+        ..Config::default()
+    })
+    .await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["RANDOMKEY"]).await;
+    assert_eq!(Frame::Null, response);
+
+    // 期限切れのキーは返さない
+    send(&mut connection, &["SET", "foo", "bar", "PX", "10"]).await;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let response = send(&mut connection, &["RANDOMKEY"]).await;
+    assert_eq!(Frame::Null, response);
+}
+
+#[tokio::test]
+async fn randomkey_returns_various_keys() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    for i in 0..10 {
+        send(&mut connection, &["SET", &format!("key:{}", i), "1"]).await;
+    }
+
+    let mut seen = vec![];
+    for _ in 0..100 {
+        match send(&mut connection, &["RANDOMKEY"]).await {
+            Frame::Bulk(key) => {
+                assert!(key.starts_with(b"key:"));
+                if !seen.contains(&key) {
+                    seen.push(key);
+                }
+            }
+            frame => panic!("{:?}", frame),
+        }
+    }
+    assert!(1 < seen.len(), "{:?}", seen);
+}
+
+#[tokio::test]
+async fn copy_duplicates_value_without_ttl() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar", "EX", "100"]).await;
+
+    let response = send(&mut connection, &["COPY", "foo", "baz"]).await;
+    assert_eq!(Frame::Integer(1), response);
+    let response = send(&mut connection, &["GET", "baz"]).await;
+    assert_eq!(Frame::Bulk("bar".into()), response);
+    let response = send(&mut connection, &["TTL", "baz"]).await;
+    assert_eq!(Frame::Integer(-1), response);
+
+    // 複製元を変更しても複製先は変わらない
+    send(&mut connection, &["APPEND", "foo", "!"]).await;
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Bulk("bar!".into()), response);
+    let response = send(&mut connection, &["GET", "baz"]).await;
+    assert_eq!(Frame::Bulk("bar".into()), response);
+}
+
+#[tokio::test]
+async fn copy_replaces_only_with_option() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "1"]).await;
+    send(&mut connection, &["SET", "bar", "2"]).await;
+
+    let response = send(&mut connection, &["COPY", "foo", "bar"]).await;
+    assert_eq!(Frame::Integer(0), response);
+    let response = send(&mut connection, &["GET", "bar"]).await;
+    assert_eq!(Frame::Bulk("2".into()), response);
+
+    let response = send(&mut connection, &["COPY", "foo", "bar", "REPLACE"]).await;
+    assert_eq!(Frame::Integer(1), response);
+    let response = send(&mut connection, &["GET", "bar"]).await;
+    assert_eq!(Frame::Bulk("1".into()), response);
+
+    let response = send(&mut connection, &["COPY", "missing", "bar", "REPLACE"]).await;
+    assert_eq!(Frame::Integer(0), response);
+    let response = send(&mut connection, &["GET", "bar"]).await;
+    assert_eq!(Frame::Bulk("1".into()), response);
+}
+
+#[tokio::test]
+async fn copy_rejects_same_key() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "1"]).await;
+
+    let response = send(&mut connection, &["COPY", "foo", "foo"]).await;
+    assert_eq!(
+        Frame::Error("ERR source and destination objects are the same".into()),
+        response
+    );
+}
+
+#[tokio::test]
+async fn touch_counts_existing_keys() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["MSET", "foo", "1", "bar", "2"]).await;
+
+    let response = send(&mut connection, &["TOUCH", "foo", "bar", "missing", "foo"]).await;
+    assert_eq!(Frame::Integer(3), response);
+
+    let response = send(&mut connection, &["TOUCH", "missing"]).await;
+    assert_eq!(Frame::Integer(0), response);
+}
+
+/// 現在のUNIX時間を秒単位で返す。
+fn unix_time() -> i64 {
+    unix_time_millis() / 1000
+}
+
+/// 現在のUNIX時間をミリ秒で返す。
+fn unix_time_millis() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+#[tokio::test]
+async fn expireat_in_past_deletes_key() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+    let past = (unix_time() - 60).to_string();
+    let response = send(&mut connection, &["EXPIREAT", "foo", &past]).await;
+    assert_eq!(Frame::Integer(1), response);
+    let response = send(&mut connection, &["EXISTS", "foo"]).await;
+    assert_eq!(Frame::Integer(0), response);
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+    let response = send(&mut connection, &["PEXPIREAT", "foo", "-1"]).await;
+    assert_eq!(Frame::Integer(1), response);
+    let response = send(&mut connection, &["EXISTS", "foo"]).await;
+    assert_eq!(Frame::Integer(0), response);
+
+    let response = send(&mut connection, &["EXPIREAT", "missing", &past]).await;
+    assert_eq!(Frame::Integer(0), response);
+}
+
+#[tokio::test]
+async fn expireat_in_near_future_expires_key() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+    let at = (unix_time_millis() + 2000).to_string();
+    let response = send(&mut connection, &["PEXPIREAT", "foo", &at]).await;
+    assert_eq!(Frame::Integer(1), response);
+
+    match send(&mut connection, &["PTTL", "foo"]).await {
+        Frame::Integer(pttl) => assert!(1000 < pttl && pttl <= 2000, "{}", pttl),
+        frame => panic!("{:?}", frame),
+    }
+
+    tokio::time::sleep(Duration::from_millis(2100)).await;
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Null, response);
+}
+
+#[tokio::test]
+async fn expireat_accepts_far_future() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+
+    // 3000年1月1日
+    let response = send(&mut connection, &["EXPIREAT", "foo", "32503680000"]).await;
+    assert_eq!(Frame::Integer(1), response);
+    match send(&mut connection, &["TTL", "foo"]).await {
+        Frame::Integer(ttl) => {
+            // 現在時刻の端数による丸めの差を許容する
+            let expected = 32503680000 - unix_time();
+            assert!((expected - 1..=expected + 1).contains(&ttl), "{}", ttl);
+        }
+        frame => panic!("{:?}", frame),
+    }
+
+    let response = send(&mut connection, &["EXPIREAT", "foo", &i64::MAX.to_string()]).await;
+    assert_eq!(
+        Frame::Error("ERR invalid expire time in 'expireat' command".into()),
+        response
+    );
+}
+
+#[tokio::test]
+async fn unlink_removes_keys() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["MSET", "foo", "1", "bar", "2"]).await;
+    send(&mut connection, &["SETRANGE", "large", "4194304", "!"]).await;
+
+    let response = send(&mut connection, &["UNLINK", "foo", "large", "missing"]).await;
+    assert_eq!(Frame::Integer(2), response);
+
+    let response = send(&mut connection, &["EXISTS", "foo", "large", "bar"]).await;
+    assert_eq!(Frame::Integer(1), response);
+}
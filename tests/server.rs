@@ -0,0 +1,352 @@
+mod common;
+
+use common::{connect, send, start_server, start_server_with};
+use my_redis::{Config, Connection, Frame};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// `OBJECT CREATEDTIME`と`OBJECT MODTIME`で取得した作成時刻と最終変更時刻を返す。
+async fn timestamps(connection: &mut Connection, key: &str) -> (i64, i64) {
+    let mut times = vec![];
+    for subcommand in ["CREATEDTIME", "MODTIME"] {
+        match send(connection, &["OBJECT", subcommand, key]).await {
+            Frame::Integer(time) => times.push(time),
+            frame => panic!("{:?}", frame),
+        }
+    }
+
+    (times[0], times[1])
+}
+
+#[tokio::test]
+async fn object_timestamps_track_creation_and_modification() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "1"]).await;
+    let (created_at, updated_at) = timestamps(&mut connection, "foo").await;
+    assert_eq!(created_at, updated_at);
+
+    // 時刻は秒単位のため、1秒以上待ってから操作する
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+
+    // 読み込みは変更時刻を更新しない
+    send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(
+        (created_at, updated_at),
+        timestamps(&mut connection, "foo").await
+    );
+
+    // 値の変更は変更時刻だけを更新する
+    send(&mut connection, &["INCR", "foo"]).await;
+    let (created, modified) = timestamps(&mut connection, "foo").await;
+    assert_eq!(created_at, created);
+    assert!(modified > updated_at, "{} -> {}", updated_at, modified);
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+
+    // 上書きは作成時刻を維持する
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+    let (created, overwritten) = timestamps(&mut connection, "foo").await;
+    assert_eq!(created_at, created);
+    assert!(overwritten > modified, "{} -> {}", modified, overwritten);
+}
+
+#[tokio::test]
+async fn object_timestamps_of_missing_key_are_null() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    for subcommand in ["CREATEDTIME", "MODTIME"] {
+        let response = send(&mut connection, &["OBJECT", subcommand, "missing"]).await;
+        assert_eq!(Frame::Null, response);
+    }
+}
+
+#[tokio::test]
+async fn debug_object_includes_timestamps() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "1"]).await;
+    let (created_at, updated_at) = timestamps(&mut connection, "foo").await;
+
+    match send(&mut connection, &["DEBUG", "OBJECT", "foo"]).await {
+        Frame::Simple(info) => {
+            assert!(info.contains("encoding:int"), "{}", info);
+            assert!(
+                info.contains(&format!("created_at:{}", created_at)),
+                "{}",
+                info
+            );
+            assert!(
+                info.contains(&format!("updated_at:{}", updated_at)),
+                "{}",
+                info
+            );
+        }
+        frame => panic!("{:?}", frame),
+    }
+
+    let response = send(&mut connection, &["DEBUG", "OBJECT", "missing"]).await;
+    assert_eq!(Frame::Error("ERR no such key".into()), response);
+}
+
+#[tokio::test]
+async fn debug_unknown_subcommand_cannot_inject_reply() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["DEBUG", "x\r\n:1337"]).await;
+    assert_eq!(
+        Frame::Error("ERR unknown subcommand 'x  :1337' for 'debug' command".into()),
+        response
+    );
+
+    let response = send(&mut connection, &["PING"]).await;
+    assert_eq!(Frame::Simple("PONG".into()), response);
+}
+
+#[tokio::test]
+async fn dbsize_counts_live_keys() {
+    // 期限切れのキーが削除されないように、定期的な削除を実質的に止める
+    let addr = start_server_with(Config {
+        sweep_interval: Duration::from_secs(3600),
+        ..Config::default()
+    })
+    .await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["DBSIZE"]).await;
+    assert_eq!(Frame::Integer(0), response);
+
+    send(&mut connection, &["MSET", "a", "1", "b", "2", "c", "3"]).await;
+    let response = send(&mut connection, &["DBSIZE"]).await;
+    assert_eq!(Frame::Integer(3), response);
+
+    send(&mut connection, &["DEL", "a"]).await;
+    let response = send(&mut connection, &["DBSIZE"]).await;
+    assert_eq!(Frame::Integer(2), response);
+
+    // 期限切れのキーは、まだ削除されていなくても数えない
+    send(&mut connection, &["SET", "d", "4", "PX", "10"]).await;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let response = send(&mut connection, &["DBSIZE"]).await;
+    assert_eq!(Frame::Integer(2), response);
+}
+
+#[tokio::test]
+async fn flushdb_removes_every_key() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    // 1回のMSETで1000個ずつ設定する
+    for chunk in 0..10 {
+        let pairs: Vec<_> = (chunk * 1000..(chunk + 1) * 1000)
+            .flat_map(|i| [format!("key:{}", i), i.to_string()])
+            .collect();
+        let mut command = vec!["MSET"];
+        command.extend(pairs.iter().map(String::as_str));
+        send(&mut connection, &command).await;
+    }
+    let response = send(&mut connection, &["DBSIZE"]).await;
+    assert_eq!(Frame::Integer(10_000), response);
+
+    let response = send(&mut connection, &["FLUSHDB"]).await;
+    assert_eq!(Frame::Simple("OK".into()), response);
+
+    let response = send(&mut connection, &["DBSIZE"]).await;
+    assert_eq!(Frame::Integer(0), response);
+    let response = send(&mut connection, &["GET", "key:0"]).await;
+    assert_eq!(Frame::Null, response);
+}
+
+#[tokio::test]
+async fn flushall_removes_keys_with_expiry() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "1", "EX", "100"]).await;
+    send(&mut connection, &["SET", "bar", "2"]).await;
+
+    let response = send(&mut connection, &["FLUSHALL"]).await;
+    assert_eq!(Frame::Simple("OK".into()), response);
+
+    let response = send(&mut connection, &["DBSIZE"]).await;
+    assert_eq!(Frame::Integer(0), response);
+    let response = send(&mut connection, &["TTL", "foo"]).await;
+    assert_eq!(Frame::Integer(-2), response);
+}
+
+#[tokio::test]
+async fn unknown_command_replies_error_and_keeps_connection() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+
+    let response = send(&mut connection, &["BOGUS", "foo"]).await;
+    assert_eq!(Frame::Error("ERR unknown command 'BOGUS'".into()), response);
+
+    // 同じコネクションで続けてコマンドを実行できる
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Bulk("bar".into()), response);
+}
+
+#[tokio::test]
+async fn malformed_command_replies_error() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    // 配列でないフレームと空の配列は、コマンドとして解釈できない
+    for frame in [Frame::Simple("GET".into()), Frame::Array(vec![])] {
+        connection.write_frame(&frame).await.unwrap();
+        match connection.read_frame().await.unwrap().unwrap() {
+            Frame::Error(msg) => assert!(msg.starts_with("ERR protocol error"), "{}", msg),
+            frame => panic!("{:?}", frame),
+        }
+    }
+
+    let response = send(&mut connection, &["PING"]).await;
+    assert_eq!(Frame::Simple("PONG".into()), response);
+}
+
+#[tokio::test]
+async fn invalid_protocol_replies_error_and_closes_connection() {
+    let addr = start_server().await;
+
+    // RESPでないインラインコマンドを送信する
+    let mut socket = TcpStream::connect(addr).await.unwrap();
+    socket.write_all(b"PING\r\n").await.unwrap();
+
+    let mut response = String::new();
+    socket.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("-ERR Protocol error"), "{}", response);
+    assert!(response.ends_with("\r\n"), "{}", response);
+
+    // サーバは新しいコネクションを受け付ける
+    let mut connection = connect(addr).await;
+    let response = send(&mut connection, &["PING"]).await;
+    assert_eq!(Frame::Simple("PONG".into()), response);
+}
+
+#[tokio::test]
+async fn unknown_command_name_cannot_inject_reply() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["FOO\r\n+OK"]).await;
+    assert_eq!(
+        Frame::Error("ERR unknown command 'FOO  +OK'".into()),
+        response
+    );
+
+    // 応答が1つだけなので、次の応答とずれない
+    let response = send(&mut connection, &["PING"]).await;
+    assert_eq!(Frame::Simple("PONG".into()), response);
+}
+
+#[tokio::test]
+async fn deeply_nested_array_replies_error_without_crashing() {
+    let addr = start_server().await;
+
+    // 入れ子の上限を大きく超える配列を送信する。サーバは途中でコネクションを閉じるため、書き込みのエラーは無視する
+    let socket = TcpStream::connect(addr).await.unwrap();
+    let (mut reader, mut writer) = socket.into_split();
+    tokio::spawn(async move {
+        let _ = writer.write_all("*1\r\n".repeat(200_000).as_bytes()).await;
+    });
+
+    let mut response = vec![];
+    let _ = reader.read_to_end(&mut response).await;
+    let response = String::from_utf8(response).unwrap();
+    assert!(
+        response.starts_with("-ERR Protocol error") && response.contains("nesting too deep"),
+        "{}",
+        response
+    );
+
+    // サーバは停止せず、新しいコネクションを受け付ける
+    let mut connection = connect(addr).await;
+    let response = send(&mut connection, &["PING"]).await;
+    assert_eq!(Frame::Simple("PONG".into()), response);
+}
+
+#[tokio::test]
+async fn object_freq_counts_accesses() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+    let response = send(&mut connection, &["OBJECT", "FREQ", "foo"]).await;
+    assert_eq!(Frame::Integer(0), response);
+
+    send(&mut connection, &["GET", "foo"]).await;
+    send(&mut connection, &["MGET", "foo", "missing"]).await;
+    send(&mut connection, &["TOUCH", "foo"]).await;
+    let response = send(&mut connection, &["OBJECT", "FREQ", "foo"]).await;
+    assert_eq!(Frame::Integer(3), response);
+}
+
+#[tokio::test]
+async fn object_idletime_resets_on_access() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+
+    let idle_time = send(&mut connection, &["OBJECT", "IDLETIME", "foo"]).await;
+    assert!(matches!(idle_time, Frame::Integer(1..)), "{:?}", idle_time);
+
+    // `OBJECT`自身はアクセスとして扱わない
+    let response = send(&mut connection, &["OBJECT", "IDLETIME", "foo"]).await;
+    assert_eq!(idle_time, response);
+
+    send(&mut connection, &["GET", "foo"]).await;
+    let response = send(&mut connection, &["OBJECT", "IDLETIME", "foo"]).await;
+    assert_eq!(Frame::Integer(0), response);
+}
+
+#[tokio::test]
+async fn object_encoding_names_value_representation() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["MSET", "int", "-42", "raw", "hello"]).await;
+
+    let response = send(&mut connection, &["OBJECT", "ENCODING", "int"]).await;
+    assert_eq!(Frame::Bulk("int".into()), response);
+    let response = send(&mut connection, &["OBJECT", "ENCODING", "raw"]).await;
+    assert_eq!(Frame::Bulk("raw".into()), response);
+    let response = send(&mut connection, &["OBJECT", "ENCODING", "missing"]).await;
+    assert_eq!(Frame::Null, response);
+}
+
+#[tokio::test]
+async fn object_rejects_invalid_arguments() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["OBJECT", "BOGUS", "foo"]).await;
+    assert_eq!(
+        Frame::Error("ERR unknown subcommand 'bogus' for 'object' command".into()),
+        response
+    );
+
+    let response = send(&mut connection, &["OBJECT", "FREQ"]).await;
+    assert_eq!(
+        Frame::Error("ERR wrong number of arguments for 'object' command".into()),
+        response
+    );
+
+    // 改行を含むサブコマンドで、応答を追加できない
+    let response = send(&mut connection, &["OBJECT", "x\r\n:1337", "foo"]).await;
+    assert_eq!(
+        Frame::Error("ERR unknown subcommand 'x  :1337' for 'object' command".into()),
+        response
+    );
+    let response = send(&mut connection, &["PING"]).await;
+    assert_eq!(Frame::Simple("PONG".into()), response);
+}
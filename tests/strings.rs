@@ -0,0 +1,792 @@
+mod common;
+
+use bytes::Bytes;
+use common::{connect, send, start_server, start_server_with};
+use my_redis::{Config, Frame};
+use std::time::Duration;
+
+#[tokio::test]
+async fn incr_and_decr_treat_missing_key_as_zero() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["INCR", "counter"]).await;
+    assert_eq!(Frame::Integer(1), response);
+
+    let response = send(&mut connection, &["DECR", "other"]).await;
+    assert_eq!(Frame::Integer(-1), response);
+
+    let response = send(&mut connection, &["GET", "other"]).await;
+    assert_eq!(Frame::Bulk("-1".into()), response);
+}
+
+#[tokio::test]
+async fn incr_rejects_non_integer_value() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+
+    let response = send(&mut connection, &["INCR", "foo"]).await;
+    assert_eq!(
+        Frame::Error("ERR value is not an integer or out of range".into()),
+        response
+    );
+
+    // 値は変更されない
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Bulk("bar".into()), response);
+}
+
+#[tokio::test]
+async fn incr_rejects_overflow() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "max", &i64::MAX.to_string()]).await;
+
+    let response = send(&mut connection, &["INCR", "max"]).await;
+    assert!(matches!(response, Frame::Error(_)), "{:?}", response);
+
+    send(&mut connection, &["SET", "min", &i64::MIN.to_string()]).await;
+
+    let response = send(&mut connection, &["DECR", "min"]).await;
+    assert!(matches!(response, Frame::Error(_)), "{:?}", response);
+}
+
+#[tokio::test]
+async fn concurrent_incr_does_not_lose_updates() {
+    let addr = start_server().await;
+
+    let tasks: Vec<_> = (0..50)
+        .map(|_| {
+            tokio::spawn(async move {
+                let mut connection = connect(addr).await;
+                send(&mut connection, &["INCR", "counter"]).await
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    let mut connection = connect(addr).await;
+    let response = send(&mut connection, &["GET", "counter"]).await;
+    assert_eq!(Frame::Bulk("50".into()), response);
+}
+
+#[tokio::test]
+async fn incrby_and_decrby_apply_delta() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    // 存在しないキーは0として扱う
+    let response = send(&mut connection, &["INCRBY", "counter", "10"]).await;
+    assert_eq!(Frame::Integer(10), response);
+
+    let response = send(&mut connection, &["INCRBY", "counter", "-15"]).await;
+    assert_eq!(Frame::Integer(-5), response);
+
+    let response = send(&mut connection, &["DECRBY", "counter", "-7"]).await;
+    assert_eq!(Frame::Integer(2), response);
+
+    let response = send(&mut connection, &["DECRBY", "missing", "3"]).await;
+    assert_eq!(Frame::Integer(-3), response);
+}
+
+#[tokio::test]
+async fn incrby_rejects_bad_delta() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["INCRBY", "counter", "ten"]).await;
+    assert_eq!(
+        Frame::Error("ERR value is not an integer or out of range".into()),
+        response
+    );
+
+    let response = send(&mut connection, &["EXISTS", "counter"]).await;
+    assert_eq!(Frame::Integer(0), response);
+}
+
+#[tokio::test]
+async fn incrby_and_decrby_detect_overflow() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(
+        &mut connection,
+        &["SET", "counter", &(i64::MAX - 1).to_string()],
+    )
+    .await;
+
+    let response = send(&mut connection, &["INCRBY", "counter", "1"]).await;
+    assert_eq!(Frame::Integer(i64::MAX), response);
+
+    let response = send(&mut connection, &["INCRBY", "counter", "1"]).await;
+    assert!(matches!(response, Frame::Error(_)), "{:?}", response);
+
+    let response = send(
+        &mut connection,
+        &["DECRBY", "counter", &i64::MIN.to_string()],
+    )
+    .await;
+    assert!(matches!(response, Frame::Error(_)), "{:?}", response);
+
+    // 失敗した操作は値を変更しない
+    let response = send(&mut connection, &["GET", "counter"]).await;
+    assert_eq!(Frame::Bulk(i64::MAX.to_string().into()), response);
+}
+
+#[tokio::test]
+async fn incrbyfloat_adds_and_formats_value() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["INCRBYFLOAT", "price", "10.5"]).await;
+    assert_eq!(Frame::Bulk("10.5".into()), response);
+
+    let response = send(&mut connection, &["INCRBYFLOAT", "price", "0.5"]).await;
+    assert_eq!(Frame::Bulk("11".into()), response);
+
+    let response = send(&mut connection, &["GET", "price"]).await;
+    assert_eq!(Frame::Bulk("11".into()), response);
+
+    // 整数の値にも加算できる
+    send(&mut connection, &["SET", "count", "3"]).await;
+    let response = send(&mut connection, &["INCRBYFLOAT", "count", "-4.25"]).await;
+    assert_eq!(Frame::Bulk("-1.25".into()), response);
+}
+
+#[tokio::test]
+async fn incrbyfloat_rejects_invalid_values() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+
+    let response = send(&mut connection, &["INCRBYFLOAT", "foo", "1"]).await;
+    assert_eq!(
+        Frame::Error("ERR value is not a valid float".into()),
+        response
+    );
+
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Bulk("bar".into()), response);
+
+    let response = send(&mut connection, &["INCRBYFLOAT", "price", "inf"]).await;
+    assert_eq!(
+        Frame::Error("ERR value is not a valid float".into()),
+        response
+    );
+
+    send(&mut connection, &["SET", "big", "1e308"]).await;
+    let response = send(&mut connection, &["INCRBYFLOAT", "big", "1e308"]).await;
+    assert_eq!(
+        Frame::Error("ERR increment would produce NaN or Infinity".into()),
+        response
+    );
+}
+
+#[tokio::test]
+async fn append_concatenates_chunks() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["APPEND", "log", "Hello"]).await;
+    assert_eq!(Frame::Integer(5), response);
+
+    let response = send(&mut connection, &["APPEND", "log", ", "]).await;
+    assert_eq!(Frame::Integer(7), response);
+
+    let response = send(&mut connection, &["APPEND", "log", "World"]).await;
+    assert_eq!(Frame::Integer(12), response);
+
+    let response = send(&mut connection, &["GET", "log"]).await;
+    assert_eq!(Frame::Bulk("Hello, World".into()), response);
+}
+
+#[tokio::test]
+async fn strlen_counts_bytes() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["STRLEN", "missing"]).await;
+    assert_eq!(Frame::Integer(0), response);
+
+    send(&mut connection, &["SET", "empty", ""]).await;
+    let response = send(&mut connection, &["STRLEN", "empty"]).await;
+    assert_eq!(Frame::Integer(0), response);
+
+    // 文字数ではなくバイト数を返す
+    send(&mut connection, &["SET", "greeting", "こんにちは"]).await;
+    let response = send(&mut connection, &["STRLEN", "greeting"]).await;
+    assert_eq!(Frame::Integer(15), response);
+}
+
+#[tokio::test]
+async fn getset_returns_previous_value_and_clears_ttl() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["GETSET", "foo", "1"]).await;
+    assert_eq!(Frame::Null, response);
+
+    send(&mut connection, &["EXPIRE", "foo", "100"]).await;
+
+    let response = send(&mut connection, &["GETSET", "foo", "2"]).await;
+    assert_eq!(Frame::Bulk("1".into()), response);
+
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Bulk("2".into()), response);
+
+    let response = send(&mut connection, &["TTL", "foo"]).await;
+    assert_eq!(Frame::Integer(-1), response);
+}
+
+#[tokio::test]
+async fn concurrent_getset_observes_exactly_one_other_value() {
+    let addr = start_server().await;
+
+    let tasks: Vec<_> = ["a", "b"]
+        .into_iter()
+        .map(|value| {
+            tokio::spawn(async move {
+                let mut connection = connect(addr).await;
+                send(&mut connection, &["GETSET", "key", value]).await
+            })
+        })
+        .collect();
+    let mut responses = Vec::new();
+    for task in tasks {
+        responses.push(task.await.unwrap());
+    }
+
+    // 先に実行された方は`Null`を、後に実行された方は先の値を受け取る
+    assert!(
+        responses == [Frame::Null, Frame::Bulk("a".into())]
+            || responses == [Frame::Bulk("b".into()), Frame::Null],
+        "{:?}",
+        responses
+    );
+}
+
+#[tokio::test]
+async fn setnx_only_sets_absent_key() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["SETNX", "lock", "first"]).await;
+    assert_eq!(Frame::Integer(1), response);
+
+    let response = send(&mut connection, &["SETNX", "lock", "second"]).await;
+    assert_eq!(Frame::Integer(0), response);
+
+    let response = send(&mut connection, &["GET", "lock"]).await;
+    assert_eq!(Frame::Bulk("first".into()), response);
+}
+
+#[tokio::test]
+async fn concurrent_setnx_has_exactly_one_winner() {
+    let addr = start_server().await;
+
+    let tasks: Vec<_> = (0..50)
+        .map(|i| {
+            tokio::spawn(async move {
+                let mut connection = connect(addr).await;
+                let owner = i.to_string();
+                let response = send(&mut connection, &["SETNX", "lock", &owner]).await;
+                (owner, response)
+            })
+        })
+        .collect();
+    let mut winners = Vec::new();
+    for task in tasks {
+        let (owner, response) = task.await.unwrap();
+        if response == Frame::Integer(1) {
+            winners.push(owner);
+        }
+    }
+    assert_eq!(1, winners.len(), "{:?}", winners);
+
+    let mut connection = connect(addr).await;
+    let response = send(&mut connection, &["GET", "lock"]).await;
+    assert_eq!(Frame::Bulk(winners[0].clone().into()), response);
+}
+
+#[tokio::test]
+async fn set_nx_and_xx_conditions() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    // キーが存在しない場合
+    let response = send(&mut connection, &["SET", "a", "1", "XX"]).await;
+    assert_eq!(Frame::Null, response);
+    let response = send(&mut connection, &["GET", "a"]).await;
+    assert_eq!(Frame::Null, response);
+
+    let response = send(&mut connection, &["SET", "a", "1", "NX"]).await;
+    assert_eq!(Frame::Simple("OK".into()), response);
+
+    // キーが存在する場合
+    let response = send(&mut connection, &["SET", "a", "2", "nx"]).await;
+    assert_eq!(Frame::Null, response);
+    let response = send(&mut connection, &["GET", "a"]).await;
+    assert_eq!(Frame::Bulk("1".into()), response);
+
+    let response = send(&mut connection, &["SET", "a", "3", "xx"]).await;
+    assert_eq!(Frame::Simple("OK".into()), response);
+    let response = send(&mut connection, &["GET", "a"]).await;
+    assert_eq!(Frame::Bulk("3".into()), response);
+}
+
+#[tokio::test]
+async fn set_rejects_nx_with_xx() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["SET", "a", "1", "NX", "XX"]).await;
+    assert_eq!(Frame::Error("ERR syntax error".into()), response);
+
+    let response = send(&mut connection, &["SET", "a", "1", "BOGUS"]).await;
+    assert_eq!(Frame::Error("ERR syntax error".into()), response);
+
+    let response = send(&mut connection, &["EXISTS", "a"]).await;
+    assert_eq!(Frame::Integer(0), response);
+}
+
+#[tokio::test]
+async fn set_px_expires_key() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["SET", "foo", "bar", "PX", "100"]).await;
+    assert_eq!(Frame::Simple("OK".into()), response);
+
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Bulk("bar".into()), response);
+
+    tokio::time::sleep(Duration::from_millis(150)).await;
+
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Null, response);
+}
+
+#[tokio::test]
+async fn set_ex_sets_ttl_and_plain_set_clears_it() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar", "EX", "100", "NX"]).await;
+
+    let response = send(&mut connection, &["TTL", "foo"]).await;
+    assert_eq!(Frame::Integer(100), response);
+
+    send(&mut connection, &["SET", "foo", "baz"]).await;
+
+    let response = send(&mut connection, &["TTL", "foo"]).await;
+    assert_eq!(Frame::Integer(-1), response);
+}
+
+#[tokio::test]
+async fn set_rejects_invalid_expire_time() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+
+    for args in [
+        ["SET", "foo", "new", "EX", "0"],
+        ["SET", "foo", "new", "PX", "-5"],
+        ["SET", "foo", "new", "EX", "9223372036854775807"],
+    ] {
+        let response = send(&mut connection, &args).await;
+        assert_eq!(
+            Frame::Error("ERR invalid expire time in 'set' command".into()),
+            response
+        );
+    }
+
+    let response = send(&mut connection, &["SET", "foo", "new", "EX", "soon"]).await;
+    assert!(matches!(response, Frame::Error(_)), "{:?}", response);
+
+    let response = send(
+        &mut connection,
+        &["SET", "foo", "new", "EX", "1", "PX", "1"],
+    )
+    .await;
+    assert_eq!(Frame::Error("ERR syntax error".into()), response);
+
+    // 失敗した`SET`はキーを変更しない
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Bulk("bar".into()), response);
+    let response = send(&mut connection, &["TTL", "foo"]).await;
+    assert_eq!(Frame::Integer(-1), response);
+}
+
+#[tokio::test]
+async fn getdel_returns_value_and_removes_key() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "token", "secret"]).await;
+
+    let response = send(&mut connection, &["GETDEL", "token"]).await;
+    assert_eq!(Frame::Bulk("secret".into()), response);
+
+    let response = send(&mut connection, &["GETDEL", "token"]).await;
+    assert_eq!(Frame::Null, response);
+
+    let response = send(&mut connection, &["EXISTS", "token"]).await;
+    assert_eq!(Frame::Integer(0), response);
+}
+
+#[tokio::test]
+async fn concurrent_getdel_delivers_value_once() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+    send(&mut connection, &["SET", "token", "secret"]).await;
+
+    let tasks: Vec<_> = (0..20)
+        .map(|_| {
+            tokio::spawn(async move {
+                let mut connection = connect(addr).await;
+                send(&mut connection, &["GETDEL", "token"]).await
+            })
+        })
+        .collect();
+    let mut received = 0;
+    for task in tasks {
+        match task.await.unwrap() {
+            Frame::Bulk(value) => {
+                assert_eq!("secret", value);
+                received += 1;
+            }
+            Frame::Null => {}
+            frame => panic!("{:?}", frame),
+        }
+    }
+
+    assert_eq!(1, received);
+}
+
+#[tokio::test]
+async fn getex_without_option_keeps_ttl() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["GETEX", "missing"]).await;
+    assert_eq!(Frame::Null, response);
+
+    send(&mut connection, &["SET", "foo", "bar", "EX", "100"]).await;
+
+    let response = send(&mut connection, &["GETEX", "foo"]).await;
+    assert_eq!(Frame::Bulk("bar".into()), response);
+
+    let response = send(&mut connection, &["TTL", "foo"]).await;
+    assert_eq!(Frame::Integer(100), response);
+}
+
+#[tokio::test]
+async fn getex_ex_extends_ttl() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar", "EX", "10"]).await;
+
+    let response = send(&mut connection, &["GETEX", "foo", "EX", "100"]).await;
+    assert_eq!(Frame::Bulk("bar".into()), response);
+
+    let response = send(&mut connection, &["TTL", "foo"]).await;
+    assert_eq!(Frame::Integer(100), response);
+}
+
+#[tokio::test]
+async fn getex_persist_keeps_key_after_original_deadline() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar", "PX", "100"]).await;
+
+    let response = send(&mut connection, &["GETEX", "foo", "PERSIST"]).await;
+    assert_eq!(Frame::Bulk("bar".into()), response);
+
+    tokio::time::sleep(Duration::from_millis(150)).await;
+
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Bulk("bar".into()), response);
+
+    let response = send(&mut connection, &["TTL", "foo"]).await;
+    assert_eq!(Frame::Integer(-1), response);
+}
+
+#[tokio::test]
+async fn getex_rejects_conflicting_options() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+
+    let response = send(&mut connection, &["GETEX", "foo", "EX", "10", "PERSIST"]).await;
+    assert_eq!(Frame::Error("ERR syntax error".into()), response);
+
+    let response = send(&mut connection, &["GETEX", "foo", "PX", "0"]).await;
+    assert_eq!(
+        Frame::Error("ERR invalid expire time in 'getex' command".into()),
+        response
+    );
+}
+
+#[tokio::test]
+async fn mget_returns_values_in_request_order() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "a", "1"]).await;
+    send(&mut connection, &["SET", "b", "2"]).await;
+    send(&mut connection, &["SET", "expired", "3", "PX", "10"]).await;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    let response = send(&mut connection, &["MGET", "b", "missing", "a", "expired"]).await;
+    assert_eq!(
+        Frame::Array(vec![
+            Frame::Bulk("2".into()),
+            Frame::Null,
+            Frame::Bulk("1".into()),
+            Frame::Null,
+        ]),
+        response
+    );
+}
+
+#[tokio::test]
+async fn mget_with_many_keys() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let keys: Vec<_> = (0..1000).map(|i| format!("key:{}", i)).collect();
+    for (i, key) in keys.iter().enumerate() {
+        // 奇数番目のキーは設定しない
+        if i % 2 == 0 {
+            send(&mut connection, &["SET", key, &i.to_string()]).await;
+        }
+    }
+
+    let mut args = vec!["MGET"];
+    args.extend(keys.iter().map(String::as_str));
+    let values = match send(&mut connection, &args).await {
+        Frame::Array(values) => values,
+        frame => panic!("{:?}", frame),
+    };
+
+    assert_eq!(1000, values.len());
+    for (i, value) in values.into_iter().enumerate() {
+        if i % 2 == 0 {
+            assert_eq!(Frame::Bulk(i.to_string().into()), value);
+        } else {
+            assert_eq!(Frame::Null, value);
+        }
+    }
+}
+
+#[tokio::test]
+async fn mset_then_mget_round_trips() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let pairs: Vec<_> = (0..100)
+        .map(|i| (format!("key:{}", i), format!("value:{}", i)))
+        .collect();
+    let mut args = vec!["MSET"];
+    for (key, value) in &pairs {
+        args.push(key);
+        args.push(value);
+    }
+    let response = send(&mut connection, &args).await;
+    assert_eq!(Frame::Simple("OK".into()), response);
+
+    let mut args = vec!["MGET"];
+    args.extend(pairs.iter().map(|(key, _)| key.as_str()));
+    let response = send(&mut connection, &args).await;
+    assert_eq!(
+        Frame::Array(
+            pairs
+                .iter()
+                .map(|(_, value)| Frame::Bulk(value.clone().into()))
+                .collect()
+        ),
+        response
+    );
+}
+
+#[tokio::test]
+async fn mset_clears_ttl_of_overwritten_keys() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "a", "old", "EX", "100"]).await;
+    send(&mut connection, &["MSET", "a", "new", "b", "2"]).await;
+
+    let response = send(&mut connection, &["TTL", "a"]).await;
+    assert_eq!(Frame::Integer(-1), response);
+}
+
+#[tokio::test]
+async fn mset_with_odd_arguments_writes_nothing() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["MSET", "a", "1", "b"]).await;
+    assert_eq!(
+        Frame::Error("ERR wrong number of arguments for 'mset' command".into()),
+        response
+    );
+
+    let response = send(&mut connection, &["EXISTS", "a", "b"]).await;
+    assert_eq!(Frame::Integer(0), response);
+}
+
+#[tokio::test]
+async fn msetnx_sets_all_keys_when_none_exist() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["MSETNX", "a", "1", "b", "2"]).await;
+    assert_eq!(Frame::Integer(1), response);
+
+    let response = send(&mut connection, &["MGET", "a", "b"]).await;
+    assert_eq!(
+        Frame::Array(vec![Frame::Bulk("1".into()), Frame::Bulk("2".into())]),
+        response
+    );
+}
+
+#[tokio::test]
+async fn msetnx_writes_nothing_when_any_key_exists() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "b", "old"]).await;
+
+    let response = send(&mut connection, &["MSETNX", "a", "1", "b", "2", "c", "3"]).await;
+    assert_eq!(Frame::Integer(0), response);
+
+    let response = send(&mut connection, &["MGET", "a", "b", "c"]).await;
+    assert_eq!(
+        Frame::Array(vec![Frame::Null, Frame::Bulk("old".into()), Frame::Null]),
+        response
+    );
+}
+
+#[tokio::test]
+async fn setrange_pads_missing_key_with_zeros() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["SETRANGE", "foo", "3", "abc"]).await;
+    assert_eq!(Frame::Integer(6), response);
+
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Bulk(Bytes::from_static(b"\0\0\0abc")), response);
+}
+
+#[tokio::test]
+async fn setrange_at_zero_on_missing_key_acts_like_set() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["SETRANGE", "foo", "0", "bar"]).await;
+    assert_eq!(Frame::Integer(3), response);
+
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Bulk("bar".into()), response);
+
+    // 空の値ではキーを作成しない
+    let response = send(&mut connection, &["SETRANGE", "empty", "0", ""]).await;
+    assert_eq!(Frame::Integer(0), response);
+    let response = send(&mut connection, &["EXISTS", "empty"]).await;
+    assert_eq!(Frame::Integer(0), response);
+}
+
+#[tokio::test]
+async fn setrange_overwrites_and_extends() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "Hello World", "EX", "100"]).await;
+
+    // 途中を上書きする
+    let response = send(&mut connection, &["SETRANGE", "foo", "6", "Redis"]).await;
+    assert_eq!(Frame::Integer(11), response);
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Bulk("Hello Redis".into()), response);
+
+    // 末尾を超えて伸ばす
+    let response = send(&mut connection, &["SETRANGE", "foo", "10", "s!!"]).await;
+    assert_eq!(Frame::Integer(13), response);
+    let response = send(&mut connection, &["GET", "foo"]).await;
+    assert_eq!(Frame::Bulk("Hello Redis!!".into()), response);
+
+    // 期限は維持する
+    match send(&mut connection, &["TTL", "foo"]).await {
+        Frame::Integer(ttl) => assert!(0 < ttl && ttl <= 100),
+        frame => panic!("{:?}", frame),
+    }
+}
+
+#[tokio::test]
+async fn setrange_rejects_invalid_offsets() {
+    let addr = start_server_with(Config {
+        max_value_len: 16,
+        ..Config::default()
+    })
+    .await;
+    let mut connection = connect(addr).await;
+
+    let response = send(&mut connection, &["SETRANGE", "foo", "-1", "bar"]).await;
+    assert_eq!(Frame::Error("ERR offset is out of range".into()), response);
+
+    let response = send(&mut connection, &["SETRANGE", "foo", "14", "bar"]).await;
+    assert_eq!(
+        Frame::Error("ERR string exceeds maximum allowed size".into()),
+        response
+    );
+
+    let response = send(&mut connection, &["SETRANGE", "foo", "13", "bar"]).await;
+    assert_eq!(Frame::Integer(16), response);
+}
+
+#[tokio::test]
+async fn getrange_returns_substring() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "This is a string"]).await;
+
+    let response = send(&mut connection, &["GETRANGE", "foo", "0", "3"]).await;
+    assert_eq!(Frame::Bulk("This".into()), response);
+
+    let response = send(&mut connection, &["GETRANGE", "foo", "-3", "-1"]).await;
+    assert_eq!(Frame::Bulk("ing".into()), response);
+
+    let response = send(&mut connection, &["GETRANGE", "foo", "0", "-1"]).await;
+    assert_eq!(Frame::Bulk("This is a string".into()), response);
+
+    let response = send(&mut connection, &["GETRANGE", "foo", "10", "100"]).await;
+    assert_eq!(Frame::Bulk("string".into()), response);
+}
+
+#[tokio::test]
+async fn getrange_returns_empty_bulk_out_of_range() {
+    let addr = start_server().await;
+    let mut connection = connect(addr).await;
+
+    send(&mut connection, &["SET", "foo", "bar"]).await;
+
+    let response = send(&mut connection, &["GETRANGE", "foo", "5", "10"]).await;
+    assert_eq!(Frame::Bulk(Bytes::new()), response);
+
+    // 存在しないキーは`Null`ではなく空の値を返す
+    let response = send(&mut connection, &["GETRANGE", "missing", "0", "-1"]).await;
+    assert_eq!(Frame::Bulk(Bytes::new()), response);
+}